@@ -0,0 +1,75 @@
+//! Compares `Datetime::from_str`, which lexes the format on every call,
+//! against a reused `CompiledFormat` taking the fixed width fast path, and
+//! a whole column parsed through `Datetime::parse_column`.
+//! Run with `cargo bench`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use datetime::datetime::{CompiledFormat, Datetime};
+
+const FORMAT: &str = "%Y%m%d%H%M%S";
+const INPUTS: &[&str] = &[
+    "20240229134510",
+    "19991231235959",
+    "20000101000000",
+    "20231015081530",
+];
+
+/// Runs `routine` over all inputs until `budget` elapses and reports the
+/// mean time per parsed input
+fn bench(name: &str, budget: Duration, mut routine: impl FnMut(&str)) {
+    let start = Instant::now();
+    let mut iterations: u32 = 0;
+    while start.elapsed() < budget {
+        for input in INPUTS {
+            routine(black_box(input));
+        }
+        iterations += 1;
+    }
+    let per_input = start.elapsed() / (iterations * INPUTS.len() as u32);
+    println!("{name:<24} {per_input:>10.2?}/iter ({iterations} rounds)");
+}
+
+/// Parses a column of `size` inputs per round, one call at a time and then
+/// all at once, and reports the throughput of each
+fn bench_column(size: usize, budget: Duration) {
+    let column = INPUTS
+        .iter()
+        .copied()
+        .cycle()
+        .take(size)
+        .collect::<Vec<_>>();
+    let measure = |name: &str, routine: &dyn Fn(&[&str])| {
+        let start = Instant::now();
+        let mut rounds: u32 = 0;
+        while start.elapsed() < budget {
+            routine(black_box(&column));
+            rounds += 1;
+        }
+        let throughput = (rounds as usize * size) as f64 / start.elapsed().as_secs_f64();
+        println!(
+            "{name:<24} {:>10.2}M inputs/s ({rounds} rounds)",
+            throughput / 1e6
+        );
+    };
+    measure("from_str per input", &|column| {
+        for input in column {
+            black_box(Datetime::from_str(input, FORMAT).unwrap());
+        }
+    });
+    measure("parse_column", &|column| {
+        black_box(Datetime::parse_column(column, FORMAT).unwrap());
+    });
+}
+
+fn main() {
+    let budget = Duration::from_secs(2);
+    bench("from_str", budget, |input| {
+        black_box(Datetime::from_str(input, FORMAT).unwrap());
+    });
+    let compiled = CompiledFormat::new(FORMAT).unwrap();
+    bench("CompiledFormat::parse", budget, |input| {
+        black_box(compiled.parse(input).unwrap());
+    });
+    bench_column(100_000, budget);
+}
@@ -1,16 +1,28 @@
 use core::fmt;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use log::{info, warn};
-use miette::{Diagnostic, Error};
+use miette::{Diagnostic, Error, SourceSpan};
 use thiserror::Error;
 
-use crate::{interpreter::Interpreter, lexer::Token};
+pub use crate::interpreter::CompiledFormat;
+use crate::{
+    duration::Duration,
+    formatter::write_tokens,
+    interpreter::Interpreter,
+    leap_seconds::LeapSecondTable,
+    lexer::{DateTimeLexer, Token},
+    locale::Locale,
+    options::{ParseOptions, Strictness, Warning},
+    timezone::{LocalResult, TzResolver, ZoneAbbreviation},
+};
 /// A datetime Structure that contains only the most important parts
 /// Every Field is public to mimic how datetime in python works.
 /// But, if you decide to build directly, there will be no guarantees
 /// that the date will be valid. So, it's recommended that you use the
 /// proper builder
-#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord)]
 pub struct Datetime {
     pub year: usize,
     pub month: usize,
@@ -18,6 +30,18 @@ pub struct Datetime {
     pub hour: usize,
     pub minute: usize,
     pub second: usize,
+    /// Fraction of the second, in nanoseconds
+    pub nanosecond: usize,
+    /// Offset from UTC in minutes, east positive, when the input gave one
+    /// through `%z`. `None` both when there was no offset and for `-00:00`,
+    /// which RFC 3339 uses for an unknown local offset. A literal `Z` in a
+    /// format only matches the letter, while `%z` reads it as UTC.
+    pub offset_minutes: Option<i32>,
+    /// The entry of [`ZoneAbbreviation::ALL`] that `%Z` matched, telling
+    /// apart e.g. `EST` from `EDT`. It carries the entry's canonical name,
+    /// so `edt` read case insensitively is kept as `EDT`, and names only a
+    /// [`TzResolver`] knows leave it `None`.
+    pub zone_abbreviation: Option<ZoneAbbreviation>,
 }
 
 /// A datetime builder that contains only the most important parts.
@@ -26,7 +50,7 @@ pub struct Datetime {
 /// field is with a wrong value, it'll throw an error.
 /// # Examples
 /// ```
-/// use doc::DatetimeBuilder
+/// use datetime::datetime::{Datetime, DatetimeBuilder};
 /// let new_date = DatetimeBuilder::new()
 ///     .year(2024)
 ///     .month(2)
@@ -34,13 +58,16 @@ pub struct Datetime {
 /// let date: Result<Datetime, _> = new_date.build();
 /// assert!(date.is_ok());
 /// ```
+#[derive(Clone)]
 pub struct DatetimeBuilder {
-    year: usize,
-    month: usize,
-    day: usize,
+    pub(crate) year: usize,
+    pub(crate) month: usize,
+    pub(crate) day: usize,
     pub(crate) hour: usize,
     minute: usize,
     second: usize,
+    nanosecond: usize,
+    offset_minutes: Option<i32>,
 }
 #[derive(Debug, Error, Diagnostic)]
 pub(crate) enum DatetimeError {
@@ -57,8 +84,377 @@ pub(crate) enum DatetimeError {
         #[source_code]
         src: Option<String>,
     },
+    #[error("Could not parse `{}` as a numeric date in `{:?}` order", src, order)]
+    UnmatchedOrder {
+        #[source_code]
+        src: String,
+        order: DateOrder,
+    },
+    #[error("The result of `{}` is outside the supported range", operation)]
+    OutOfRange { operation: String },
+    #[error("Invalid timestamp `{}`: {}", src, reason)]
+    InvalidTimestamp { src: String, reason: String },
+    #[error(
+        "Expected ASCII input, found byte `{:#04x}` at position {}",
+        byte,
+        position
+    )]
+    NonAsciiInput { byte: u8, position: usize },
+    #[error("`{}` does not match any of the common date formats", src)]
+    #[diagnostic(help("Use `Datetime::from_str` with an explicit format"))]
+    Unrecognized {
+        #[source_code]
+        src: String,
+    },
+    #[error("`{}` has no `{}` separating the ends of a range", src, separator)]
+    MissingSeparator {
+        separator: String,
+        #[source_code]
+        src: String,
+    },
+    #[error(
+        "`{}` has {} bytes, but the record should have {}",
+        src,
+        src.len(),
+        expected
+    )]
+    UnexpectedLength {
+        expected: usize,
+        #[source_code]
+        src: String,
+    },
+    #[error(
+        "Columns {}..{} are past the end of `{}`, which has {} bytes",
+        start,
+        end,
+        src,
+        src.len()
+    )]
+    ColumnOutOfBounds {
+        start: usize,
+        end: usize,
+        #[source_code]
+        src: String,
+    },
+    #[error("Invalid encoded date: {}", reason)]
+    InvalidEncoding { reason: String },
+    #[error("`{}` is not the name of a {}", name, kind)]
+    UnknownName { name: String, kind: &'static str },
+    #[error("`{}` is not a relative date", src)]
+    #[diagnostic(help(
+        "Use `today`, `yesterday`, `tomorrow` or a signed offset like `+3d`, with units s, m, h, d or w"
+    ))]
+    InvalidRelative {
+        #[source_code]
+        src: String,
+    },
+    #[error("Dates do not keep a `{}`, so it cannot be formatted", field)]
+    Unformattable { field: Token },
+}
+
+/// Why [`Datetime::checked_rfc3339`] rejected a timestamp
+#[derive(Debug, Diagnostic, Error)]
+pub enum Rfc3339Error {
+    /// The input is not laid out like `2024-05-17T13:45:10Z`
+    #[error("Expected {}", expected)]
+    Malformed {
+        expected: &'static str,
+        #[source_code]
+        src: String,
+        #[label("Here")]
+        at: SourceSpan,
+    },
+    /// The month or the day does not exist, e.g.: the 30th of February
+    #[error("Invalid date: {}", reason)]
+    InvalidDate {
+        reason: String,
+        #[source_code]
+        src: String,
+        #[label("This date")]
+        at: SourceSpan,
+    },
+    /// The hour, minute or second is past its maximum
+    #[error("Invalid time: {}", reason)]
+    InvalidTime {
+        reason: String,
+        #[source_code]
+        src: String,
+        #[label("This time")]
+        at: SourceSpan,
+    },
+    /// A fraction of a second without digits, or without seconds before it
+    #[error("Invalid fraction of a second: {}", reason)]
+    InvalidFraction {
+        reason: &'static str,
+        #[source_code]
+        src: String,
+        #[label("This fraction")]
+        at: SourceSpan,
+    },
+    /// The offset is missing, malformed or a day or more away from UTC
+    #[error("Invalid UTC offset")]
+    #[diagnostic(help("End the timestamp with `Z` or an offset like `+05:30`"))]
+    InvalidOffset {
+        #[source_code]
+        src: String,
+        #[label("This offset")]
+        at: SourceSpan,
+    },
+}
+
+/// The order in which day, month and year appear in an all-numeric date.
+/// Used to resolve dates like `01/02/03`, which are valid under more than
+/// one layout.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DateOrder {
+    /// Day, month, year. e.g.: `31/12/2024`
+    Dmy,
+    /// Month, day, year. e.g.: `12/31/2024`
+    Mdy,
+    /// Year, month, day. e.g.: `2024/12/31`
+    Ymd,
+}
+
+impl DateOrder {
+    /// Formats tried, in order, for this layout. Four digit years come
+    /// first, so `01/02/2003` is never read as a two digit year.
+    fn formats(self) -> &'static [&'static str] {
+        match self {
+            Self::Dmy => &[
+                "%d/%m/%Y", "%d-%m-%Y", "%d.%m.%Y", "%d/%m/%y", "%d-%m-%y", "%d.%m.%y",
+            ],
+            Self::Mdy => &[
+                "%m/%d/%Y", "%m-%d-%Y", "%m.%d.%Y", "%m/%d/%y", "%m-%d-%y", "%m.%d.%y",
+            ],
+            Self::Ymd => &[
+                "%Y/%m/%d", "%Y-%m-%d", "%Y.%m.%d", "%y/%m/%d", "%y-%m-%d", "%y.%m.%d",
+            ],
+        }
+    }
+}
+
+/// A unit of calendar or clock time, from the largest to the smallest
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TimeUnit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+/// One of the numeric fields of a [`Datetime`], as passed to
+/// [`Datetime::map_fields`]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Field {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Nanosecond,
+}
+
+/// Every field of a [`Datetime`] along with values derived from them,
+/// returned by [`Datetime::to_parts`]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct DateTimeParts {
+    pub year: usize,
+    pub month: usize,
+    pub day: usize,
+    pub hour: usize,
+    pub minute: usize,
+    pub second: usize,
+    pub nanosecond: usize,
+    pub offset_minutes: Option<i32>,
+    pub weekday: Weekday,
+    /// From 1 for the 1st of January
+    pub day_of_year: u16,
+    /// From 1 for January to March
+    pub quarter: u8,
+}
+
+/// A set of [`Datetime`] fields, combined with `|`, e.g.: to leave them
+/// out of [`Datetime::cmp_ignoring`]
+/// # Examples
+/// ```
+/// use datetime::datetime::FieldMask;
+/// let mask = FieldMask::SECOND | FieldMask::NANOSECOND;
+/// assert!(mask.contains(FieldMask::SECOND));
+/// assert!(!mask.contains(FieldMask::MINUTE));
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct FieldMask(u8);
+
+impl FieldMask {
+    pub const NONE: FieldMask = FieldMask(0);
+    pub const YEAR: FieldMask = FieldMask(1 << 0);
+    pub const MONTH: FieldMask = FieldMask(1 << 1);
+    pub const DAY: FieldMask = FieldMask(1 << 2);
+    pub const HOUR: FieldMask = FieldMask(1 << 3);
+    pub const MINUTE: FieldMask = FieldMask(1 << 4);
+    pub const SECOND: FieldMask = FieldMask(1 << 5);
+    pub const NANOSECOND: FieldMask = FieldMask(1 << 6);
+    pub const OFFSET: FieldMask = FieldMask(1 << 7);
+    /// Every field in `other` is also in this mask
+    pub const fn contains(self, other: FieldMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+    /// The mask as an integer, one bit per field from the year at bit 0,
+    /// e.g.: to pass it across an FFI boundary
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+/// The fields rejected by [`Datetime::checked_from_parts`], all at once
+pub type FieldErrors = FieldMask;
+
+impl std::ops::BitOr for FieldMask {
+    type Output = FieldMask;
+
+    fn bitor(self, other: FieldMask) -> FieldMask {
+        FieldMask(self.0 | other.0)
+    }
+}
+
+/// Whether the ends of a range are part of it
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Bounds {
+    /// Both `start` and `end` are in the range
+    Inclusive,
+    /// Neither `start` nor `end` is in the range
+    Exclusive,
+}
+
+/// A day of the week, in ISO 8601 order from Monday
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub(crate) const ALL: [Weekday; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+    /// ISO 8601 number of the weekday, from 1 for Monday to 7 for Sunday
+    pub fn number_from_monday(self) -> usize {
+        self as usize + 1
+    }
+}
+
+/// The English name, e.g.: `Monday`
+impl fmt::Display for Weekday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Locale::ENGLISH.weekdays[*self as usize])
+    }
+}
+
+/// Reads the English name, in full or abbreviated and ignoring ASCII case
+impl std::str::FromStr for Weekday {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let locale = Locale::ENGLISH;
+        (0..7)
+            .find(|&index| {
+                locale.weekdays[index].eq_ignore_ascii_case(name)
+                    || locale.short_weekdays[index].eq_ignore_ascii_case(name)
+            })
+            .map(|index| Self::ALL[index])
+            .ok_or_else(|| {
+                DatetimeError::UnknownName {
+                    name: name.to_string(),
+                    kind: "weekday",
+                }
+                .into()
+            })
+    }
+}
+
+/// A month of the year, from January
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Month {
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+impl Month {
+    pub(crate) const ALL: [Month; 12] = [
+        Self::January,
+        Self::February,
+        Self::March,
+        Self::April,
+        Self::May,
+        Self::June,
+        Self::July,
+        Self::August,
+        Self::September,
+        Self::October,
+        Self::November,
+        Self::December,
+    ];
+    /// Number of the month, from 1 for January to 12 for December
+    pub fn number(self) -> usize {
+        self as usize + 1
+    }
+}
+
+/// The English name, e.g.: `January`
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Locale::ENGLISH.months[*self as usize])
+    }
+}
+
+/// Reads the English name, in full or abbreviated and ignoring ASCII case
+impl std::str::FromStr for Month {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let locale = Locale::ENGLISH;
+        (0..12)
+            .find(|&index| {
+                locale.months[index].eq_ignore_ascii_case(name)
+                    || locale.short_months[index].eq_ignore_ascii_case(name)
+            })
+            .map(|index| Self::ALL[index])
+            .ok_or_else(|| {
+                DatetimeError::UnknownName {
+                    name: name.to_string(),
+                    kind: "month",
+                }
+                .into()
+            })
+    }
 }
 
+/// 1900-01-01 00:00:00, the date fields left unset by a format start from.
+/// See [`Datetime::zeroed`] and [`Datetime::epoch`] for other origins.
 impl Default for Datetime {
     fn default() -> Self {
         Self {
@@ -68,10 +464,27 @@ impl Default for Datetime {
             hour: 0,
             minute: 00,
             second: 00,
+            nanosecond: 0,
+            offset_minutes: None,
+            zone_abbreviation: None,
         }
     }
 }
 
+/// Parses any of the formats of [`Datetime::try_guess`]
+impl TryFrom<&str> for Datetime {
+    type Error = Error;
+
+    fn try_from(date: &str) -> Result<Self, Self::Error> {
+        Self::try_guess(date).ok_or_else(|| {
+            DatetimeError::Unrecognized {
+                src: date.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
 impl fmt::Display for Datetime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -91,6 +504,8 @@ impl Default for DatetimeBuilder {
             hour: 0,
             minute: 00,
             second: 00,
+            nanosecond: 0,
+            offset_minutes: None,
         }
     }
 }
@@ -99,6 +514,24 @@ impl DatetimeBuilder {
     pub fn new() -> Self {
         Self::default()
     }
+    /// Moves a day past the end of its month back to the last day, e.g.:
+    /// the 31st of April to the 30th
+    pub(crate) fn clamp_day(self) -> Self {
+        match days_in_month(self.year, self.month) {
+            Some(max_days) if self.day > max_days => Self {
+                day: max_days,
+                ..self
+            },
+            _ => self,
+        }
+    }
+    /// Moves a month past December back to it
+    pub(crate) fn clamp_month(self) -> Self {
+        Self {
+            month: self.month.min(12),
+            ..self
+        }
+    }
     pub fn year(self, year: usize) -> Self {
         Self { year, ..self }
     }
@@ -120,6 +553,17 @@ impl DatetimeBuilder {
     pub fn second(self, second: usize) -> Self {
         Self { second, ..self }
     }
+
+    pub fn nanosecond(self, nanosecond: usize) -> Self {
+        Self { nanosecond, ..self }
+    }
+    /// Offset from UTC, east positive. Must be less than a day either way.
+    pub fn offset_minutes(self, offset_minutes: i32) -> Self {
+        Self {
+            offset_minutes: Some(offset_minutes),
+            ..self
+        }
+    }
     /// Returns an error if some field for the date is invalid, e.g.: month(14)
     pub fn build(self) -> Result<Datetime, Error> {
         let max_days = match days_in_month(self.year, self.month) {
@@ -166,7 +610,7 @@ impl DatetimeBuilder {
         if self.minute > 59 {
             return Err(DatetimeError::InvalidValue {
                 expected: "0-60".to_string(),
-                field: Token::Day,
+                field: Token::Minute,
                 got: self.minute.to_string(),
                 src: None,
             }
@@ -181,6 +625,26 @@ impl DatetimeBuilder {
             }
             .into());
         }
+        if let Some(offset) = self.offset_minutes
+            && offset.unsigned_abs() >= 24 * 60
+        {
+            return Err(DatetimeError::InvalidValue {
+                expected: "An offset within ±23:59".to_string(),
+                field: Token::UtcOffset,
+                got: offset.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        if self.nanosecond > 999_999_999 {
+            return Err(DatetimeError::InvalidValue {
+                expected: "0-999999999 nanoseconds".to_string(),
+                field: Token::Second,
+                got: self.nanosecond.to_string(),
+                src: None,
+            }
+            .into());
+        }
         Ok(Datetime {
             year: self.year,
             month: self.month,
@@ -188,11 +652,14 @@ impl DatetimeBuilder {
             hour: self.hour,
             minute: self.minute,
             second: self.second,
+            nanosecond: self.nanosecond,
+            offset_minutes: self.offset_minutes,
+            zone_abbreviation: None,
         })
     }
 }
 fn is_leap_year(year: usize) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
 }
 fn days_in_month(year: usize, month: usize) -> Option<usize> {
     match month {
@@ -202,12 +669,373 @@ fn days_in_month(year: usize, month: usize) -> Option<usize> {
         _ => None,                               // Invalid month
     }
 }
+/// The last year whose dates all have a Unix timestamp that fits in an
+/// `i64`, give or take the last weeks of the year
+const MAX_YEAR: usize = 292_277_026_596;
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Unix timestamp of the GPS epoch, 1980-01-06 00:00:00
+const GPS_EPOCH: i64 = 315_964_800;
+/// Days from the Unix epoch back to 1899-12-30, day 0 of Excel serials
+const EXCEL_EPOCH_DAYS: i64 = -25_569;
+/// Number of days between 1970-01-01 and the given date, in the proleptic
+/// Gregorian calendar. Based on Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: usize, day: usize) -> i64 {
+    let (month, day) = (month as i64, day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 }; // March = 0
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+/// ISO 8601 weekday, from 1 for Monday to 7 for Sunday, of a day count
+/// as returned by [`days_from_civil`]
+fn iso_weekday_from_days(days: i64) -> i64 {
+    // 1970-01-01 was a Thursday
+    (days + 3).rem_euclid(7) + 1
+}
+/// Days from the start of the week, Monday if `monday_first` and Sunday
+/// otherwise, to an ISO 8601 weekday
+fn days_since_week_start(iso_weekday: usize, monday_first: bool) -> usize {
+    if monday_first {
+        iso_weekday - 1
+    } else {
+        iso_weekday % 7
+    }
+}
+/// Number of ISO 8601 weeks (52 or 53) in an ISO week-numbering year.
+/// Years whose 1st of January is a Thursday, or a Wednesday in leap
+/// years, have 53.
+fn iso_weeks_in_year(year: i64) -> i64 {
+    let jan_first = iso_weekday_from_days(days_from_civil(year, 1, 1));
+    let leap = year >= 0 && is_leap_year(year as usize);
+    if jan_first == 4 || (leap && jan_first == 3) {
+        53
+    } else {
+        52
+    }
+}
+/// Inverse of [`days_from_civil`], returning `(year, month, day)`
+fn civil_from_days(days: i64) -> (i64, usize, usize) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153; // March = 0
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as usize;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as usize;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Whether two intervals share any instant. Intervals are half-open,
+/// including their start but not their end, so back to back bookings like
+/// 10:00-11:00 and 11:00-12:00 do not overlap, and an interval whose end is
+/// not after its start is empty.
+/// # Examples
+/// ```
+/// use datetime::datetime::{Datetime, intervals_overlap};
+/// let at = |hour| Datetime::try_from_ymd_hms(2024, 1, 1, hour, 0, 0).unwrap();
+/// assert!(intervals_overlap((at(9), at(11)), (at(10), at(12))));
+/// assert!(!intervals_overlap((at(10), at(11)), (at(11), at(12))));
+/// ```
+pub fn intervals_overlap(a: (Datetime, Datetime), b: (Datetime, Datetime)) -> bool {
+    a.0 < a.1 && b.0 < b.1 && a.0 < b.1 && b.0 < a.1
+}
+
+/// The weekday of a date, without building a [`Datetime`]. Returns `None`
+/// if the date does not exist.
+/// # Examples
+/// ```
+/// use datetime::datetime::{Weekday, weekday_of};
+/// assert_eq!(weekday_of(2024, 2, 29), Some(Weekday::Thursday));
+/// assert_eq!(weekday_of(2023, 2, 29), None);
+/// ```
+pub fn weekday_of(year: usize, month: usize, day: usize) -> Option<Weekday> {
+    let max_days = days_in_month(year, month)?;
+    if day == 0 || day > max_days {
+        return None;
+    }
+    let days = days_from_civil(i64::try_from(year).ok()?, month, day);
+    Some(Weekday::ALL[iso_weekday_from_days(days) as usize - 1])
+}
 
 impl Datetime {
+    /// The latest date with a Unix timestamp that fits in an `i64`
+    pub const MAX: Datetime = Datetime {
+        year: MAX_YEAR,
+        month: 12,
+        day: 4,
+        hour: 15,
+        minute: 30,
+        second: 7,
+        nanosecond: 0,
+        offset_minutes: None,
+        zone_abbreviation: None,
+    };
     pub fn from_str(date: &str, date_format: &str) -> Result<Self, Error> {
-        Interpreter::parse_datetime(date, date_format)
+        Self::parse_datetime_with_options(date, date_format, &ParseOptions::default())
+    }
+    /// Same as [`Datetime::from_str`], with the matching rules adjusted by
+    /// `options`
+    pub fn parse_datetime_with_options(
+        date: &str,
+        date_format: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        Interpreter::parse_datetime_with_options(date, date_format, options)
+    }
+    /// Same as [`Datetime::parse_datetime_with_options`], also returning
+    /// what lenient parsing had to accept, in the order it came up. Strict
+    /// parsing never warns.
+    /// # Examples
+    /// ```
+    /// use datetime::{
+    ///     datetime::Datetime,
+    ///     options::{ParseOptions, Strictness, Warning},
+    /// };
+    /// let options = ParseOptions {
+    ///     strictness: Strictness::Lenient,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let (date, warnings) = Datetime::parse_with_warnings("2024-13-01", "%Y-%m-%d", &options).unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2024, 12, 1));
+    /// assert_eq!(warnings, vec![Warning::MonthClamped { got: 13 }]);
+    /// ```
+    pub fn parse_with_warnings(
+        date: &str,
+        date_format: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<Warning>), Error> {
+        Interpreter::parse_datetime_with_warnings(date, date_format, options)
+    }
+    /// Like [`Datetime::from_str`], but first checks that `input` is
+    /// `expected_input_len` bytes long, so truncated or padded records in
+    /// fixed width files fail before any field is read
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// assert!(Datetime::parse_exact_len("20240229", "%Y%m%d", 8).is_ok());
+    /// assert!(Datetime::parse_exact_len("2024022", "%Y%m%d", 8).is_err());
+    /// ```
+    pub fn parse_exact_len(
+        input: &str,
+        date_format: &str,
+        expected_input_len: usize,
+    ) -> Result<Self, Error> {
+        if input.len() != expected_input_len {
+            return Err(DatetimeError::UnexpectedLength {
+                expected: expected_input_len,
+                src: input.to_string(),
+            }
+            .into());
+        }
+        Self::from_str(input, date_format)
+    }
+    /// Parses a column of inputs sharing `date_format`, lexing it only once.
+    /// See [`CompiledFormat::parse_column`]. Fails as a whole only if the
+    /// format is invalid, otherwise each input gets its own result.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let dates = Datetime::parse_column(&["20240131", "2024013"], "%Y%m%d").unwrap();
+    /// assert!(dates[0].is_ok() && dates[1].is_err());
+    /// ```
+    pub fn parse_column(
+        inputs: &[&str],
+        date_format: &str,
+    ) -> Result<Vec<Result<Self, Error>>, Error> {
+        Ok(CompiledFormat::new(date_format)?.parse_column(inputs))
+    }
+    /// Parses both ends of a range like `2024-01-01 to 2024-12-31`, split
+    /// at the first `separator` and each read with `date_format`
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let (start, end) = Datetime::parse_range("2024-01-01 to 2024-12-31", "%Y-%m-%d", " to ").unwrap();
+    /// assert_eq!((start.month, end.month), (1, 12));
+    /// ```
+    pub fn parse_range(
+        input: &str,
+        date_format: &str,
+        separator: &str,
+    ) -> Result<(Self, Self), Error> {
+        let (start, end) =
+            input
+                .split_once(separator)
+                .ok_or_else(|| DatetimeError::MissingSeparator {
+                    separator: separator.to_string(),
+                    src: input.to_string(),
+                })?;
+        Ok((
+            Self::from_str(start, date_format)?,
+            Self::from_str(end, date_format)?,
+        ))
+    }
+    /// Parses a date laid out by position, without separators, as in some
+    /// legacy files. Each `(field, start, len)` reads the digits in
+    /// `input[start..start + len]`, counting from 0. A month or day left out
+    /// of `spec` is 1, any other field is 0.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Field};
+    /// let spec = [(Field::Year, 0, 4), (Field::Month, 4, 2), (Field::Day, 6, 2)];
+    /// let date = Datetime::parse_fixed_columns("20240229", &spec).unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+    /// ```
+    pub fn parse_fixed_columns(input: &str, spec: &[(Field, usize, usize)]) -> Result<Self, Error> {
+        let mut builder = DatetimeBuilder::new().month(1).day(1);
+        for &(field, start, len) in spec {
+            let end = start.saturating_add(len);
+            let digits = input
+                .get(start..end)
+                .ok_or_else(|| DatetimeError::ColumnOutOfBounds {
+                    start,
+                    end,
+                    src: input.to_string(),
+                })?;
+            let token = match field {
+                Field::Year => Token::FullYear,
+                Field::Month => Token::FullMonth,
+                Field::Day => Token::Day,
+                Field::Hour => Token::Hour,
+                Field::Minute => Token::Minute,
+                Field::Second => Token::Second,
+                Field::Nanosecond => Token::Fraction,
+            };
+            let value = digits
+                .bytes()
+                .all(|byte| byte.is_ascii_digit())
+                .then(|| digits.parse::<usize>().ok())
+                .flatten()
+                .ok_or_else(|| DatetimeError::InvalidValue {
+                    expected: format!("{len} digits"),
+                    field: token,
+                    got: digits.to_string(),
+                    src: Some(input.to_string()),
+                })?;
+            builder = match field {
+                Field::Year => builder.year(value),
+                Field::Month => builder.month(value),
+                Field::Day => builder.day(value),
+                Field::Hour => builder.hour(value),
+                Field::Minute => builder.minute(value),
+                Field::Second => builder.second(value),
+                Field::Nanosecond => builder.nanosecond(value),
+            };
+        }
+        builder.build()
+    }
+    /// Parses `date` with `primary`, or with `fallback` if that fails, e.g.:
+    /// for ISO 8601 dates mixed with a legacy layout. If neither matches,
+    /// the error of `primary` is returned, as the expected format explains
+    /// best what is wrong.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::parse_or("17/05/2024", "%Y-%m-%d", "%d/%m/%Y").unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2024, 5, 17));
+    /// ```
+    pub fn parse_or(date: &str, primary: &str, fallback: &str) -> Result<Self, Error> {
+        Self::from_str(date, primary).or_else(|error| {
+            warn!("Format `{primary}` did not match `{date}`. Reason: {error}");
+            Self::from_str(date, fallback).map_err(|_| error)
+        })
+    }
+    /// Same as [`Datetime::from_str`], but as forgiving as possible: with
+    /// [`Strictness::Lenient`] matching, names in any case and whitespace
+    /// around the input trimmed
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::parse_lenient("1/2/24", "%d/%m/%y").unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2024, 2, 1));
+    /// ```
+    ///
+    /// [`Strictness::Lenient`]: crate::options::Strictness::Lenient
+    pub fn parse_lenient(date: &str, date_format: &str) -> Result<Self, Error> {
+        let options = ParseOptions {
+            strictness: Strictness::Lenient,
+            case_insensitive: true,
+            trim_input: true,
+            ..ParseOptions::default()
+        };
+        Self::parse_datetime_with_options(date, date_format, &options)
+    }
+    /// Same as [`Datetime::from_str`], but for the end of a date range: the
+    /// time left out of the input is the last second of the day
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let end = Datetime::parse_as_range_end("2024-01-01", "%Y-%m-%d").unwrap();
+    /// assert_eq!(end.to_string(), "01/01/2024 23:59:59");
+    /// ```
+    pub fn parse_as_range_end(date: &str, date_format: &str) -> Result<Self, Error> {
+        let options = ParseOptions {
+            end_of_day: true,
+            ..ParseOptions::default()
+        };
+        Self::parse_datetime_with_options(date, date_format, &options)
+    }
+    /// Same as [`Datetime::from_str`], but matching names such as `%p`
+    /// markers with the words of `locale`
+    pub fn parse_datetime_with_locale(
+        date: &str,
+        date_format: &str,
+        locale: &Locale,
+    ) -> Result<Self, Error> {
+        let options = ParseOptions {
+            locale: *locale,
+            ..ParseOptions::default()
+        };
+        Self::parse_datetime_with_options(date, date_format, &options)
+    }
+    /// Same as [`Datetime::from_str`], but reading the date straight from
+    /// bytes, e.g.: a network or file buffer. The bytes are validated once,
+    /// as UTF-8, and only ASCII input is accepted: the first other byte is
+    /// reported with its position.
+    pub fn parse_bytes(date: &[u8], date_format: &str) -> Result<Self, Error> {
+        let non_ascii = |position: usize| DatetimeError::NonAsciiInput {
+            byte: date[position],
+            position,
+        };
+        let text = std::str::from_utf8(date).map_err(|error| {
+            // Invalid UTF-8 always has a byte past the ASCII range
+            non_ascii(
+                date.iter()
+                    .position(|byte| !byte.is_ascii())
+                    .unwrap_or(error.valid_up_to()),
+            )
+        })?;
+        if let Some(position) = text.bytes().position(|byte| !byte.is_ascii()) {
+            return Err(non_ascii(position).into());
+        }
+        Self::from_str(text, date_format)
     }
     pub fn try_guess(date: &str) -> Option<Self> {
+        Self::try_guess_with_options(date, &ParseOptions::default())
+    }
+    /// Same as [`Datetime::try_guess`], with the matching rules adjusted by
+    /// `options`, e.g.: a [`ParseOptions::century_pivot`] suited to the
+    /// records being read for the two digit year formats
+    /// # Examples
+    /// ```
+    /// use datetime::{datetime::Datetime, options::ParseOptions};
+    /// let options = ParseOptions {
+    ///     century_pivot: 100,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let date = Datetime::try_guess_with_options("99-12-31", &options).unwrap();
+    /// assert_eq!(date.year, 2099);
+    /// ```
+    pub fn try_guess_with_options(date: &str, options: &ParseOptions) -> Option<Self> {
         const COMMON_FORMATS: &[&str] = &[
             "%Y/%m/%d",
             "%Y-%m-%d",
@@ -217,6 +1045,10 @@ impl Datetime {
             "%y-%m-%d",
             "%y/%d/%m",
             "%y/%d/%m",
+            "%d/%m/%Y",
+            "%d-%m-%Y",
+            "%d/%m/%y",
+            "%d-%m-%y",
             "%H:%M:%S",
             "%Hh:%Mm:%Ss",
             "%H %p:%M:%S",
@@ -227,73 +1059,3751 @@ impl Datetime {
         ];
         for format in COMMON_FORMATS {
             info!("Trying to parse `{date}` as format `{format}`");
-            match Interpreter::parse_datetime(date, format) {
+            match Interpreter::parse_datetime_with_options(date, format, options) {
                 Ok(date) => return Some(date),
                 Err(e) => warn!("Format `{format}` did not match `{date}`. Reason: {e}"),
             }
         }
         None
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use miette::Error;
-
-    type TestResult = Result<(), Error>;
-
-    #[test]
-    fn test_from_str() -> TestResult {
-        let date = Datetime::from_str("2023-10-15", "%Y-%m-%d")?;
-        assert_eq!(date.year, 2023);
-        assert_eq!(date.month, 10);
-        assert_eq!(date.day, 15);
-
-        let date = Datetime::from_str("15/10/2023", "%d/%m/%Y")?;
-        assert_eq!(date.year, 2023);
-        assert_eq!(date.month, 10);
-        assert_eq!(date.day, 15);
-
-        Ok(())
+    /// Same as [`Datetime::try_guess`], but ambiguous all-numeric dates are
+    /// first read in the [`Locale::date_order`] of `locale`, whose words
+    /// are then used for the remaining formats
+    /// # Examples
+    /// ```
+    /// use datetime::{
+    ///     datetime::{DateOrder, Datetime},
+    ///     locale::Locale,
+    /// };
+    /// let locale = Locale {
+    ///     date_order: DateOrder::Dmy,
+    ///     ..Locale::default()
+    /// };
+    /// let date = Datetime::try_guess_with_locale("01/02/2024", &locale).unwrap();
+    /// assert_eq!((date.month, date.day), (2, 1));
+    /// ```
+    pub fn try_guess_with_locale(date: &str, locale: &Locale) -> Option<Self> {
+        Self::parse_with_order(date, locale.date_order)
+            .ok()
+            .or_else(|| {
+                let options = ParseOptions {
+                    locale: *locale,
+                    ..ParseOptions::default()
+                };
+                Self::try_guess_with_options(date, &options)
+            })
     }
-
-    #[test]
-    fn test_default() {
-        let date = Datetime::default();
-        assert_eq!(date.year, 1900);
-        assert_eq!(date.month, 1);
-        assert_eq!(date.day, 1);
-        assert_eq!(date.hour, 0);
-        assert_eq!(date.minute, 0);
-        assert_eq!(date.second, 0);
+    /// Parses an RFC 3339 timestamp, such as `2024-05-17T13:45:10.5+05:30`.
+    /// The `T` may also be a space, letters may be lowercase and the
+    /// fraction of a second is optional. So are the seconds, as some
+    /// profiles leave them out, e.g.: `2024-05-17T13:45Z`. An offset of
+    /// `-00:00` leaves [`Datetime::offset_minutes`] unset.
+    /// Errors are an [`Rfc3339Error`], see [`Datetime::checked_rfc3339`].
+    pub fn parse_rfc3339(date: &str) -> Result<Self, Error> {
+        Ok(Self::checked_rfc3339(date)?)
     }
-
-    #[test]
-    fn test_invalid_formats() {
-        let result = Datetime::from_str("2023-13-32", "%Y-%m-%d");
-        assert!(result.is_err()); // Invalid month and day
-
-        let _ = Datetime::from_str("25:70:99", "%H:%M:%S");
+    /// Same as [`Datetime::parse_rfc3339`], but returning why the timestamp
+    /// was rejected, so callers can tell a bad offset from a bad date
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Rfc3339Error};
+    /// assert!(matches!(
+    ///     Datetime::checked_rfc3339("2024-02-30T00:00:00Z"),
+    ///     Err(Rfc3339Error::InvalidDate { .. })
+    /// ));
+    /// assert!(matches!(
+    ///     Datetime::checked_rfc3339("2024-02-29T00:00:00+25:00"),
+    ///     Err(Rfc3339Error::InvalidOffset { .. })
+    /// ));
+    /// ```
+    pub fn checked_rfc3339(date: &str) -> Result<Self, Rfc3339Error> {
+        let bytes = date.as_bytes();
+        let src = || date.to_string();
+        let number = |start: usize, len: usize| {
+            let digits = date.get(start..start + len)?;
+            digits
+                .bytes()
+                .all(|byte| byte.is_ascii_digit())
+                .then(|| digits.parse::<usize>().ok())
+                .flatten()
+        };
+        let malformed = |expected, at: usize| Rfc3339Error::Malformed {
+            expected,
+            src: src(),
+            at: (at.min(date.len()), 0).into(),
+        };
+        let (Some(year), Some(b'-'), Some(month), Some(b'-'), Some(day)) = (
+            number(0, 4),
+            bytes.get(4),
+            number(5, 2),
+            bytes.get(7),
+            number(8, 2),
+        ) else {
+            return Err(malformed("a date like `2024-05-17`", 0));
+        };
+        if !matches!(bytes.get(10), Some(b'T' | b't' | b' ')) {
+            return Err(malformed("`T` or a space after the date", 10));
+        }
+        let (Some(hour), Some(b':'), Some(minute)) = (number(11, 2), bytes.get(13), number(14, 2))
+        else {
+            return Err(malformed("a time like `13:45:10`", 11));
+        };
+        let mut position = 16;
+        let second = match bytes.get(position) {
+            Some(b':') => {
+                position += 3;
+                Some(number(17, 2).ok_or_else(|| malformed("two digits of seconds", 17))?)
+            }
+            _ => None,
+        };
+        let time_end = position;
+        let mut nanosecond = 0;
+        if bytes.get(position) == Some(&b'.') {
+            let digits = bytes[position + 1..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count();
+            let invalid_fraction = |reason| Rfc3339Error::InvalidFraction {
+                reason,
+                src: src(),
+                at: (position, digits + 1).into(),
+            };
+            if second.is_none() {
+                return Err(invalid_fraction("it needs the seconds before it"));
+            }
+            if digits == 0 {
+                return Err(invalid_fraction("it has no digits"));
+            }
+            // Digits past nanoseconds are dropped
+            let fraction = &date[position + 1..position + 1 + digits.min(9)];
+            nanosecond = format!("{fraction:0<9}").parse().expect("Nine digits");
+            position += 1 + digits;
+        }
+        let zone = &date[position..];
+        let invalid_offset = || Rfc3339Error::InvalidOffset {
+            src: src(),
+            at: (position, zone.len()).into(),
+        };
+        let offset_minutes = match zone.as_bytes() {
+            [b'Z' | b'z'] => Some(0),
+            [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+                let (Some(hours @ 0..=23), Some(minutes @ 0..=59)) =
+                    (number(position + 1, 2), number(position + 4, 2))
+                else {
+                    return Err(invalid_offset());
+                };
+                let offset = (hours * 60 + minutes) as i32;
+                match sign {
+                    // `-00:00` means the offset is unknown
+                    b'-' if offset == 0 => None,
+                    b'-' => Some(-offset),
+                    _ => Some(offset),
+                }
+            }
+            _ => return Err(invalid_offset()),
+        };
+        let invalid_date = |reason| Rfc3339Error::InvalidDate {
+            reason,
+            src: src(),
+            at: (0, 10).into(),
+        };
+        let max_days = days_in_month(year, month)
+            .ok_or_else(|| invalid_date(format!("there is no month {month}")))?;
+        if day == 0 || day > max_days {
+            return Err(invalid_date(format!(
+                "{year}-{month:02} has days 1-{max_days}, not {day}"
+            )));
+        }
+        let invalid_time = |reason| Rfc3339Error::InvalidTime {
+            reason,
+            src: src(),
+            at: (11, time_end - 11).into(),
+        };
+        let second = second.unwrap_or(0);
+        if hour > 23 {
+            return Err(invalid_time(format!("hour {hour} is past 23")));
+        }
+        if minute > 59 {
+            return Err(invalid_time(format!("minute {minute} is past 59")));
+        }
+        if second > 59 {
+            return Err(invalid_time(format!("second {second} is past 59")));
+        }
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset_minutes,
+            zone_abbreviation: None,
+        })
     }
-
-    // Add this when you implement the try_guess feature
-    #[test]
-    fn test_try_guess() {
-        let date = "2023-10-15";
-        let result = Datetime::try_guess(date);
-        assert!(result.is_some());
-        if let Some(dt) = result {
-            assert_eq!(dt.year, 2023);
-            assert_eq!(dt.month, 10);
-            assert_eq!(dt.day, 15);
+    /// Same as [`Datetime::parse_rfc3339`], named for code that relies on
+    /// the full nanosecond precision being kept: all 9 digits of the
+    /// fraction end up in [`Datetime::nanosecond`], none are rounded away.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::from_rfc3339_nanos("2024-01-01T00:00:00.123456789Z").unwrap();
+    /// assert_eq!(date.nanosecond, 123_456_789);
+    /// ```
+    pub fn from_rfc3339_nanos(date: &str) -> Result<Self, Error> {
+        Self::parse_rfc3339(date)
+    }
+    /// Parses an RFC 2822 date, as used by email and HTTP headers, such as
+    /// `Fri, 17 May 2024 13:45:10 +0000`. The weekday and the seconds are
+    /// optional, and the obsolete `GMT` and `UT` zones are read as UTC.
+    pub fn parse_rfc2822(date: &str) -> Result<Self, Error> {
+        const FORMAT: &str = "[%a, ]%e %b %Y %H:%M[:%S]";
+        match date
+            .strip_suffix(" GMT")
+            .or_else(|| date.strip_suffix(" UT"))
+        {
+            Some(date) => Ok(Self {
+                offset_minutes: Some(0),
+                ..Self::from_str(date, FORMAT)?
+            }),
+            None => Self::from_str(date, &format!("{FORMAT} %z")),
         }
-
-        let date = "15/10/2023";
-        let result = Datetime::try_guess(date);
-        assert!(result.is_some());
-
-        let date = "not a date";
-        let result = Datetime::try_guess(date);
-        assert!(result.is_none());
+    }
+    /// Best effort parsing of a date in any of the supported layouts: RFC
+    /// 3339, RFC 2822, a Unix timestamp in seconds or milliseconds, or one
+    /// of the formats of [`Datetime::try_guess`].
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// for date in ["2024-05-17T00:00:00Z", "1715904000", "17/05/2024"] {
+    ///     let date = Datetime::parse_any(date).unwrap();
+    ///     assert_eq!((date.year, date.month, date.day), (2024, 5, 17));
+    /// }
+    /// ```
+    pub fn parse_any(date: &str) -> Option<Self> {
+        Self::parse_rfc3339(date)
+            .or_else(|_| Self::parse_rfc2822(date))
+            .or_else(|_| Self::from_timestamp_auto(date))
+            .ok()
+            .or_else(|| Self::try_guess(date))
+    }
+    /// Parses an all-numeric date, using `order` to decide which number is
+    /// the day, the month and the year. Separators may be `/`, `-` or `.`,
+    /// and the year may have either two or four digits.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{DateOrder, Datetime};
+    /// let date = Datetime::parse_with_order("01/02/03", DateOrder::Dmy).unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2003, 2, 1));
+    /// ```
+    pub fn parse_with_order(date: &str, order: DateOrder) -> Result<Self, Error> {
+        for format in order.formats() {
+            info!("Trying to parse `{date}` as format `{format}`");
+            match Interpreter::parse_datetime(date, format) {
+                Ok(date) => return Ok(date),
+                Err(e) => warn!("Format `{format}` did not match `{date}`. Reason: {e}"),
+            }
+        }
+        Err(DatetimeError::UnmatchedOrder {
+            src: date.to_string(),
+            order,
+        }
+        .into())
+    }
+    /// Adds (or, if negative, subtracts) `months` calendar months. When the
+    /// resulting month is shorter than the current day, the day is clamped
+    /// to the last day of that month, e.g.: 2024-01-31 + 1 month = 2024-02-29.
+    /// Returns an error before year 0 or past year 292277026596.
+    pub fn add_months(&self, months: i64) -> Result<Self, Error> {
+        let total = (self.year as i64)
+            .checked_mul(12)
+            .and_then(|m| m.checked_add(self.month as i64 - 1))
+            .and_then(|m| m.checked_add(months))
+            .filter(|m| (0..=MAX_YEAR as i64 * 12 + 11).contains(m))
+            .ok_or(DatetimeError::OutOfRange {
+                operation: format!("{self} + {months} months"),
+            })?;
+        let year = (total / 12) as usize;
+        let month = (total % 12) as usize + 1;
+        let max_days = days_in_month(year, month).expect("Month is always between 1-12");
+        Ok(Self {
+            year,
+            month,
+            day: self.day.min(max_days),
+            ..*self
+        })
+    }
+    /// Number of whole `unit`s from this date to `as_of`, negative if
+    /// `as_of` is earlier. A unit only counts once complete, so from
+    /// 2000-05-17 to 2024-05-16 is 23 years. Months are counted like
+    /// [`Datetime::add_months`], so from a 31st to the last day of a shorter
+    /// month is a whole month. Offsets are ignored.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, TimeUnit};
+    /// let birth = Datetime::from_ymd(2000, 5, 17).unwrap();
+    /// let now = Datetime::from_ymd(2024, 5, 16).unwrap();
+    /// assert_eq!(birth.age_in_units(&now, TimeUnit::Year), 23);
+    /// assert_eq!(birth.age_in_units(&now, TimeUnit::Month), 287);
+    /// ```
+    pub fn age_in_units(&self, as_of: &Datetime, unit: TimeUnit) -> i64 {
+        let seconds = as_of.to_unix_timestamp() - self.to_unix_timestamp();
+        let length = match unit {
+            TimeUnit::Year | TimeUnit::Month => {
+                let months = |date: &Datetime| date.year as i64 * 12 + date.month as i64;
+                let mut count = months(as_of) - months(self);
+                let shifted = self.add_months(count).expect("Between two valid dates");
+                match shifted.cmp_ignoring(as_of, FieldMask::OFFSET) {
+                    std::cmp::Ordering::Greater if count > 0 => count -= 1,
+                    std::cmp::Ordering::Less if count < 0 => count += 1,
+                    _ => {}
+                }
+                return if unit == TimeUnit::Year {
+                    count / 12
+                } else {
+                    count
+                };
+            }
+            TimeUnit::Day => SECONDS_PER_DAY,
+            TimeUnit::Hour => 3600,
+            TimeUnit::Minute => 60,
+            TimeUnit::Second => 1,
+        };
+        seconds / length
+    }
+    /// Midnight of the given date, validated like [`DatetimeBuilder::build`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// assert!(Datetime::from_ymd(2024, 2, 29).is_ok());
+    /// assert!(Datetime::from_ymd(2024, 2, 30).is_err());
+    /// ```
+    pub fn from_ymd(year: usize, month: usize, day: usize) -> Result<Self, Error> {
+        DatetimeBuilder::new()
+            .year(year)
+            .month(month)
+            .day(day)
+            .build()
+    }
+    /// Midnight of the `ordinal`-th day of `year`, from 1 for the 1st of
+    /// January to 365, or 366 in leap years
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::checked_from_ordinal(2024, 366).unwrap();
+    /// assert_eq!((date.month, date.day), (12, 31));
+    /// assert!(Datetime::checked_from_ordinal(2023, 366).is_err());
+    /// ```
+    pub fn checked_from_ordinal(year: usize, ordinal: u16) -> Result<Self, Error> {
+        let length = if is_leap_year(year) { 366 } else { 365 };
+        if ordinal == 0 || ordinal > length {
+            return Err(DatetimeError::InvalidValue {
+                expected: format!("A day of the year between 1-{length}"),
+                field: Token::DayOfYear,
+                got: ordinal.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        Self::from_ymd(year, 1, 1)?.add_days(i64::from(ordinal) - 1)
+    }
+    /// Builds a date from its year, month, day, hour, minute and second,
+    /// in that order, reporting every invalid one instead of only the
+    /// first. Meant for C style APIs, where a bitmask crosses the boundary
+    /// more easily than an error message. A day is checked against the
+    /// length of its month only when the month is valid, and otherwise
+    /// against the longest one.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, FieldMask};
+    /// let errors = Datetime::checked_from_parts(&[2024, 13, 40, 0, 0, 0]).unwrap_err();
+    /// assert_eq!(errors, FieldMask::MONTH | FieldMask::DAY);
+    /// assert_eq!(errors.bits(), 0b110);
+    /// ```
+    pub fn checked_from_parts(parts: &[usize; 6]) -> Result<Self, FieldErrors> {
+        let [year, month, day, hour, minute, second] = *parts;
+        let max_days = days_in_month(year, month).unwrap_or(31);
+        let errors = [
+            (FieldMask::MONTH, (1..=12).contains(&month)),
+            (FieldMask::DAY, (1..=max_days).contains(&day)),
+            (FieldMask::HOUR, hour < 24),
+            (FieldMask::MINUTE, minute < 60),
+            (FieldMask::SECOND, second < 60),
+        ]
+        .into_iter()
+        .filter(|(_, valid)| !valid)
+        .fold(FieldMask::NONE, |errors, (field, _)| errors | field);
+        if errors != FieldMask::NONE {
+            return Err(errors);
+        }
+        Ok(DatetimeBuilder::new()
+            .year(year)
+            .month(month)
+            .day(day)
+            .hour(hour)
+            .minute(minute)
+            .second(second)
+            .build()
+            .expect("Every field was checked"))
+    }
+    /// Builds a date from its parts, validating all of them like
+    /// [`DatetimeBuilder::build`]. Negative values are reported as invalid
+    /// instead of wrapping around.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::try_from_ymd_hms(2024, 2, 29, 13, 45, 10).unwrap();
+    /// assert_eq!(date.to_string(), "29/02/2024 13:45:10");
+    /// assert!(Datetime::try_from_ymd_hms(2023, 2, 29, 0, 0, 0).is_err());
+    /// ```
+    pub fn try_from_ymd_hms(
+        year: i64,
+        month: i64,
+        day: i64,
+        hour: i64,
+        minute: i64,
+        second: i64,
+    ) -> Result<Self, Error> {
+        let unsigned = |value: i64, field: Token| {
+            usize::try_from(value).map_err(|_| DatetimeError::InvalidValue {
+                expected: "A non negative value".to_string(),
+                field,
+                got: value.to_string(),
+                src: None,
+            })
+        };
+        DatetimeBuilder::new()
+            .year(unsigned(year, Token::FullYear)?)
+            .month(unsigned(month, Token::FullMonth)?)
+            .day(unsigned(day, Token::Day)?)
+            .hour(unsigned(hour, Token::Hour)?)
+            .minute(unsigned(minute, Token::Minute)?)
+            .second(unsigned(second, Token::Second)?)
+            .build()
+    }
+    /// The smallest valid date, 0000-01-01 00:00:00. Unlike
+    /// [`Datetime::default`], which is in 1900, every field is at its
+    /// minimum.
+    pub const fn zeroed() -> Self {
+        Self {
+            year: 0,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            offset_minutes: None,
+            zone_abbreviation: None,
+        }
+    }
+    /// The Unix epoch, 1970-01-01 00:00:00
+    pub const fn epoch() -> Self {
+        Self {
+            year: 1970,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            offset_minutes: None,
+            zone_abbreviation: None,
+        }
+    }
+    /// Packs the date and time into a single integer that sorts like the
+    /// dates do, e.g.: for keys of an ordered store. The fraction of a
+    /// second and the offset are not kept, so dates differing only in those
+    /// get the same key. Reversed by [`Datetime::from_monotonic_key`].
+    /// # Panics
+    /// If the key does not fit in a `u64`, which only happens past the year
+    /// 573935436382, far beyond [`Datetime::MAX`].
+    pub fn monotonic_key(&self) -> u64 {
+        // Every field is a digit in a mixed radix number, from the year
+        // down to the second, so comparing keys compares fields in order
+        [
+            (self.month - 1, 12),
+            (self.day - 1, 31),
+            (self.hour, 24),
+            (self.minute, 60),
+            (self.second, 60),
+        ]
+        .into_iter()
+        .try_fold(self.year as u64, |key, (digit, radix)| {
+            key.checked_mul(radix)?.checked_add(digit as u64)
+        })
+        .expect("Key does not fit in a u64")
+    }
+    /// Inverse of [`Datetime::monotonic_key`]. Returns an error if the key
+    /// does not decode to a valid date.
+    pub fn from_monotonic_key(key: u64) -> Result<Self, Error> {
+        let (key, second) = (key / 60, key % 60);
+        let (key, minute) = (key / 60, key % 60);
+        let (key, hour) = (key / 24, key % 24);
+        let (key, day) = (key / 31, key % 31 + 1);
+        let (year, month) = (key / 12, key % 12 + 1);
+        DatetimeBuilder::new()
+            .year(year as usize)
+            .month(month as usize)
+            .day(day as usize)
+            .hour(hour as usize)
+            .minute(minute as usize)
+            .second(second as usize)
+            .build()
+    }
+    /// Length of [`Datetime::to_bytes`]
+    pub const ENCODED_LEN: usize = 20;
+    /// Packs every field into little endian bytes: the year in 8, the month,
+    /// day, hour, minute and second in 1 each, the nanosecond in 4, then a
+    /// byte telling if there is an offset and the offset minutes in 2.
+    /// Reversed by [`Datetime::from_bytes`].
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[..8].copy_from_slice(&(self.year as u64).to_le_bytes());
+        for (byte, field) in
+            bytes[8..13]
+                .iter_mut()
+                .zip([self.month, self.day, self.hour, self.minute, self.second])
+        {
+            *byte = field as u8;
+        }
+        bytes[13..17].copy_from_slice(&(self.nanosecond as u32).to_le_bytes());
+        if let Some(offset) = self.offset_minutes {
+            bytes[17] = 1;
+            bytes[18..].copy_from_slice(&(offset as i16).to_le_bytes());
+        }
+        bytes
+    }
+    /// Inverse of [`Datetime::to_bytes`]. The fields are validated like
+    /// [`DatetimeBuilder::build`], so corrupted bytes give an error rather
+    /// than an invalid date.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let invalid = |reason: String| DatetimeError::InvalidEncoding { reason };
+        let bytes: &[u8; Self::ENCODED_LEN] = bytes.try_into().map_err(|_| {
+            invalid(format!(
+                "Expected {} bytes but got {}",
+                Self::ENCODED_LEN,
+                bytes.len()
+            ))
+        })?;
+        let year = u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"));
+        let year =
+            usize::try_from(year).map_err(|_| invalid(format!("Year {year} is too large")))?;
+        let field = |index: usize| usize::from(bytes[index]);
+        let builder = DatetimeBuilder::new()
+            .year(year)
+            .month(field(8))
+            .day(field(9))
+            .hour(field(10))
+            .minute(field(11))
+            .second(field(12))
+            .nanosecond(u32::from_le_bytes(bytes[13..17].try_into().expect("4 bytes")) as usize);
+        let offset = i16::from_le_bytes(bytes[18..].try_into().expect("2 bytes"));
+        match bytes[17] {
+            0 => builder.build(),
+            1 => builder.offset_minutes(i32::from(offset)).build(),
+            flag => Err(invalid(format!("Unknown offset flag {flag}")).into()),
+        }
+    }
+    /// Whole seconds elapsed since the Unix epoch (1970-01-01 00:00:00),
+    /// treating the fields as UTC. Any offset is ignored, see
+    /// [`Datetime::as_utc_timestamp`] for the instant it names. Dates before
+    /// the epoch are negative. The fraction of a second is dropped.
+    /// # Panics
+    /// If the timestamp does not fit in an `i64`, which only happens past
+    /// the year 292277026596. See [`Datetime::checked_timestamp`].
+    pub fn to_unix_timestamp(&self) -> i64 {
+        self.checked_timestamp()
+            .expect("Timestamp does not fit in an i64")
+    }
+    /// Same as [`Datetime::to_unix_timestamp`], but returning an error
+    /// instead of overflowing for dates too far in the future
+    pub fn checked_timestamp(&self) -> Result<i64, Error> {
+        let out_of_range = || DatetimeError::OutOfRange {
+            operation: format!("Unix timestamp of {self}"),
+        };
+        // Past this year even the day count in seconds overflows, so it is
+        // checked before doing any calendar math
+        const LAST_YEAR: i64 = i64::MAX / SECONDS_PER_DAY / 365;
+        let year = i64::try_from(self.year)
+            .ok()
+            .filter(|year| *year <= LAST_YEAR)
+            .ok_or_else(out_of_range)?;
+        let days = days_from_civil(year, self.month, self.day);
+        let time = self
+            .hour
+            .checked_mul(3600)
+            .zip(self.minute.checked_mul(60))
+            .and_then(|(hours, minutes)| hours.checked_add(minutes)?.checked_add(self.second))
+            .and_then(|time| i64::try_from(time).ok())
+            .ok_or_else(out_of_range)?;
+        days.checked_mul(SECONDS_PER_DAY)
+            .and_then(|seconds| seconds.checked_add(time))
+            .ok_or_else(|| out_of_range().into())
+    }
+    /// Whole seconds since the Unix epoch of the moment this date names,
+    /// taking [`Datetime::offset_minutes`] into account, unlike
+    /// [`Datetime::to_unix_timestamp`]. The same instant written with
+    /// different offsets gives the same value. Dates without an offset are
+    /// taken as UTC.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::parse_rfc3339("1970-01-01T02:00:00+02:00").unwrap();
+    /// assert_eq!(date.as_utc_timestamp(), 0);
+    /// assert_eq!(date.to_unix_timestamp(), 7200);
+    /// ```
+    pub fn as_utc_timestamp(&self) -> i64 {
+        let offset = i64::from(self.offset_minutes.unwrap_or(0)) * 60;
+        self.to_unix_timestamp()
+            .checked_sub(offset)
+            .expect("Timestamp does not fit in an i64")
+    }
+    /// Inverse of [`Datetime::to_unix_timestamp`]. Returns an error if the
+    /// timestamp falls before year 0.
+    pub fn from_unix_timestamp(timestamp: i64) -> Result<Self, Error> {
+        let (year, month, day) = civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY));
+        if year < 0 {
+            return Err(DatetimeError::OutOfRange {
+                operation: format!("Unix timestamp {timestamp}"),
+            }
+            .into());
+        }
+        let seconds = timestamp.rem_euclid(SECONDS_PER_DAY) as usize;
+        Ok(Self {
+            year: year as usize,
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds % 3600 / 60,
+            second: seconds % 60,
+            nanosecond: 0,
+            offset_minutes: None,
+            zone_abbreviation: None,
+        })
+    }
+    /// Like [`Datetime::from_unix_timestamp`], but counting milliseconds
+    pub fn from_unix_millis(millis: i64) -> Result<Self, Error> {
+        let datetime = Self::from_unix_timestamp(millis.div_euclid(1000))?;
+        Ok(Self {
+            nanosecond: millis.rem_euclid(1000) as usize * 1_000_000,
+            ..datetime
+        })
+    }
+    /// The current time from the system clock, in UTC and without an
+    /// offset. A clock set before 1970 gives the Unix epoch.
+    #[cfg(feature = "std")]
+    pub fn now() -> Self {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let seconds = i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX);
+        Self {
+            nanosecond: elapsed.subsec_nanos() as usize,
+            ..Self::from_unix_timestamp(seconds).expect("Timestamps after 1970 are in range")
+        }
+    }
+    /// Describes how long after `earlier` this date is, in its largest
+    /// whole unit from seconds to days: `3 days ago`, or `in 2 hours` if
+    /// `earlier` is actually later. Dates in the same second are `just now`.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let now = Datetime::from_ymd(2024, 1, 4).unwrap();
+    /// let then = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// assert_eq!(now.format_duration_since(&then), "3 days ago");
+    /// assert_eq!(then.format_duration_since(&now), "in 3 days");
+    /// ```
+    pub fn format_duration_since(&self, earlier: &Datetime) -> String {
+        let seconds = self.to_unix_timestamp() - earlier.to_unix_timestamp();
+        let (amount, unit) = [(SECONDS_PER_DAY, "day"), (3600, "hour"), (60, "minute")]
+            .into_iter()
+            .map(|(length, unit)| (seconds.abs() / length, unit))
+            .find(|(amount, _)| *amount > 0)
+            .unwrap_or((seconds.abs(), "second"));
+        let plural = if amount == 1 { "" } else { "s" };
+        match seconds {
+            0 => "just now".to_string(),
+            1.. => format!("{amount} {unit}{plural} ago"),
+            _ => format!("in {amount} {unit}{plural}"),
+        }
+    }
+    /// The date `t` of the way from `a` to `b`, to the nanosecond, where `t`
+    /// is clamped to `0.0..=1.0`. Works on Unix timestamps, so the fields are
+    /// read as UTC like in [`Datetime::to_unix_timestamp`], and the result
+    /// keeps the offset and zone abbreviation of `a`.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let midnight = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// let next_midnight = Datetime::from_ymd(2024, 1, 2).unwrap();
+    /// let noon = Datetime::interpolate(midnight, next_midnight, 0.5);
+    /// assert_eq!(noon.hour, 12);
+    /// ```
+    pub fn interpolate(a: Datetime, b: Datetime, t: f64) -> Self {
+        const NANOS: i128 = 1_000_000_000;
+        let nanos = |date: &Datetime| {
+            i128::from(date.to_unix_timestamp()) * NANOS + date.nanosecond as i128
+        };
+        let (start, end) = (nanos(&a), nanos(&b));
+        let (span, t) = (end - start, t.clamp(0.0, 1.0));
+        // Floats lose the last digits of long spans, so they must not step
+        // past either end, and the end itself is exact
+        let offset = match t {
+            1.0 => span,
+            _ => ((span as f64 * t).round() as i128).clamp(span.min(0), span.max(0)),
+        };
+        let point = start + offset;
+        let seconds = i64::try_from(point.div_euclid(NANOS)).expect("Between two valid dates");
+        Self {
+            nanosecond: point.rem_euclid(NANOS) as usize,
+            offset_minutes: a.offset_minutes,
+            zone_abbreviation: a.zone_abbreviation,
+            ..Self::from_unix_timestamp(seconds).expect("Between two valid dates")
+        }
+    }
+    /// The point nearest to `self` on the grid that starts at `origin` and
+    /// repeats every `step`, either way in time. Halfway between two points
+    /// it snaps to the later one, and the offset is kept from `self`. A zero
+    /// step only has `origin` on its grid.
+    /// # Examples
+    /// ```
+    /// use datetime::{datetime::Datetime, duration::Duration};
+    /// let midnight = Datetime::from_ymd(2024, 3, 1).unwrap();
+    /// let date = Datetime::from_str("2024-03-01 12:07", "%Y-%m-%d %H:%M").unwrap();
+    /// let snapped = date.snap_to_grid(midnight, Duration::minutes(15));
+    /// assert_eq!((snapped.hour, snapped.minute), (12, 0));
+    /// ```
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`], or if
+    /// the nearest point falls before year 0
+    pub fn snap_to_grid(&self, origin: Datetime, step: Duration) -> Self {
+        const NANOS: i128 = 1_000_000_000;
+        let nanos = |date: &Datetime| {
+            i128::from(date.to_unix_timestamp()) * NANOS + date.nanosecond as i128
+        };
+        let step = i128::from(step.as_seconds()).abs() * NANOS;
+        let elapsed = nanos(self) - nanos(&origin);
+        let steps = match step {
+            0 => 0,
+            _ => (elapsed + step / 2).div_euclid(step),
+        };
+        let seconds = i64::try_from(steps * step / NANOS).expect("Grid point fits in an i64");
+        let snapped = origin
+            .add_seconds(seconds)
+            .expect("Grid point after year 0");
+        Self {
+            offset_minutes: self.offset_minutes,
+            zone_abbreviation: self.zone_abbreviation,
+            ..snapped
+        }
+    }
+    /// Seconds since the GPS epoch, 1980-01-06 00:00:00, treating the
+    /// fields as UTC. Leap seconds are not counted, so the result is behind
+    /// true GPS time by the leap seconds since 1980 (18 as of 2017); add
+    /// [`Datetime::leap_seconds_between`] the epoch and this date, over a
+    /// [`LeapSecondTable`], to correct it.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    pub fn to_gps_seconds(&self) -> i64 {
+        self.to_unix_timestamp() - GPS_EPOCH
+    }
+    /// Inverse of [`Datetime::to_gps_seconds`]
+    pub fn from_gps_seconds(seconds: i64) -> Result<Self, Error> {
+        let timestamp =
+            seconds
+                .checked_add(GPS_EPOCH)
+                .ok_or_else(|| DatetimeError::OutOfRange {
+                    operation: format!("GPS time {seconds}"),
+                })?;
+        Self::from_unix_timestamp(timestamp)
+    }
+    /// Excel serial date: days since 1899-12-30, with the time of day as the
+    /// fraction. To stay compatible with Lotus 1-2-3, Excel counts a 29th of
+    /// February 1900 that never existed, so dates before 1900-03-01 are one
+    /// day lower than the real count, making 1900-01-01 serial 1.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// assert_eq!(date.to_excel_serial(), 45292.0);
+    /// ```
+    pub fn to_excel_serial(&self) -> f64 {
+        const MARCH_1900: Datetime = Datetime {
+            year: 1900,
+            month: 3,
+            ..Datetime::zeroed()
+        };
+        let days = if *self < MARCH_1900 {
+            EXCEL_EPOCH_DAYS + 1
+        } else {
+            EXCEL_EPOCH_DAYS
+        };
+        let seconds = self.to_unix_timestamp() as f64 + self.nanosecond as f64 / 1e9;
+        seconds / SECONDS_PER_DAY as f64 - days as f64
+    }
+    /// Inverse of [`Datetime::to_excel_serial`], to the millisecond. Serial
+    /// 60, the 29th of February 1900 that Excel made up, is an error.
+    pub fn from_excel_serial(serial: f64) -> Result<Self, Error> {
+        let out_of_range = || DatetimeError::OutOfRange {
+            operation: format!("Excel serial {serial}"),
+        };
+        const MILLIS_PER_DAY: i64 = SECONDS_PER_DAY * 1000;
+        let millis = (serial * MILLIS_PER_DAY as f64).round();
+        if !millis.is_finite() || millis.abs() >= i64::MAX as f64 {
+            return Err(out_of_range().into());
+        }
+        let millis = millis as i64;
+        let days = match millis.div_euclid(MILLIS_PER_DAY) {
+            60 => return Err(out_of_range().into()),
+            days if days < 60 => EXCEL_EPOCH_DAYS + 1,
+            _ => EXCEL_EPOCH_DAYS,
+        };
+        let millis = days
+            .checked_mul(MILLIS_PER_DAY)
+            .and_then(|offset| millis.checked_add(offset))
+            .ok_or_else(out_of_range)?;
+        Self::from_unix_millis(millis)
+    }
+    /// Parses a Unix timestamp of unknown unit, telling seconds and
+    /// milliseconds apart by the number of digits: 10 digits are seconds
+    /// (years 2001 to 2286) and 13 digits are milliseconds. Any other length
+    /// is rejected instead of guessed.
+    pub fn from_timestamp_auto(timestamp: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| DatetimeError::InvalidTimestamp {
+            src: timestamp.to_string(),
+            reason: reason.to_string(),
+        };
+        if !timestamp.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid("It must only contain digits").into());
+        }
+        match timestamp.len() {
+            10 => Self::from_unix_timestamp(timestamp.parse().expect("Ten digits fit in i64")),
+            13 => Self::from_unix_millis(timestamp.parse().expect("Thirteen digits fit in i64")),
+            _ => Err(invalid("Expected 10 digits for seconds or 13 for milliseconds").into()),
+        }
+    }
+    /// The ISO 8601 week-numbering year and week (1 to 53) of this date.
+    /// Weeks start on Monday and week 1 is the one containing the year's
+    /// first Thursday, so dates near New Year may belong to a week of the
+    /// neighbouring year, e.g.: 2024-12-30 is in week 1 of 2025.
+    pub fn iso_year_week(&self) -> (i32, u8) {
+        let year = self.year as i64;
+        let days = days_from_civil(year, self.month, self.day);
+        let ordinal = days - days_from_civil(year, 1, 1) + 1;
+        let week = (ordinal - iso_weekday_from_days(days) + 10) / 7;
+        let (year, week) = if week < 1 {
+            (year - 1, iso_weeks_in_year(year - 1))
+        } else if week > iso_weeks_in_year(year) {
+            (year + 1, 1)
+        } else {
+            (year, week)
+        };
+        (year as i32, week as u8)
+    }
+    /// Inverse of [`Datetime::iso_year_week`], giving midnight of `weekday`
+    /// in that week. Returns an error if the year has no such week.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Weekday};
+    /// let date = Datetime::from_iso_year_week(2025, 1, Weekday::Monday).unwrap();
+    /// assert_eq!((date.year, date.month, date.day), (2024, 12, 30));
+    /// ```
+    pub fn from_iso_year_week(year: i32, week: u8, weekday: Weekday) -> Result<Self, Error> {
+        let year = i64::from(year);
+        if week == 0 || i64::from(week) > iso_weeks_in_year(year) {
+            return Err(DatetimeError::InvalidValue {
+                expected: format!("A week between 1-{}", iso_weeks_in_year(year)),
+                field: Token::IsoWeek,
+                got: week.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        // The 4th of January is always in week 1
+        let fourth = days_from_civil(year, 1, 4);
+        let monday = fourth - iso_weekday_from_days(fourth) + 1;
+        let days = monday + (i64::from(week) - 1) * 7 + weekday as i64;
+        Self::epoch().add_days(days)
+    }
+    /// ISO 8601 weekday, from 1 for Monday to 7 for Sunday
+    pub(crate) fn iso_weekday(&self) -> usize {
+        iso_weekday_from_days(days_from_civil(self.year as i64, self.month, self.day)) as usize
+    }
+    /// Writes the date following `date_format`, using the same specifiers
+    /// as [`Datetime::from_str`]. Names come from [`Locale::ENGLISH`].
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let date = Datetime::from_str("2024-01-01 13:05", "%Y-%m-%d %H:%M").unwrap();
+    /// assert_eq!(date.format("%c").unwrap(), "Mon Jan  1 13:05:00 2024");
+    /// assert_eq!(date.format("%d %B, %I%p").unwrap(), "01 January, 01PM");
+    /// ```
+    pub fn format(&self, date_format: &str) -> Result<String, Error> {
+        let mut formatted = String::new();
+        self.format_into(&mut formatted, date_format)?;
+        Ok(formatted)
+    }
+    /// Same as [`Datetime::format`], but appending to `buf` so one buffer
+    /// can be reused across many dates. On error, `buf` may hold part of
+    /// the output.
+    pub fn format_into(&self, buf: &mut String, date_format: &str) -> Result<(), Error> {
+        let tokens = DateTimeLexer::new(date_format).collect::<Result<Vec<_>, _>>()?;
+        write_tokens(buf, self, &tokens, &Locale::default())
+    }
+    /// Same as [`Datetime::format`], but with the names of `%A`, `%a`, `%B`,
+    /// `%b` and `%p`, and the layout of `%c`, from `locale`
+    /// # Examples
+    /// ```
+    /// use datetime::{datetime::Datetime, locale::Locale};
+    /// let date = Datetime::from_ymd(2024, 3, 4).unwrap();
+    /// let formatted = date.format_localized("%A, %d %B", &Locale::PORTUGUESE).unwrap();
+    /// assert_eq!(formatted, "Segunda-feira, 04 Março");
+    /// ```
+    pub fn format_localized(&self, date_format: &str, locale: &Locale) -> Result<String, Error> {
+        let tokens = DateTimeLexer::new(date_format).collect::<Result<Vec<_>, _>>()?;
+        let mut formatted = String::new();
+        write_tokens(&mut formatted, self, &tokens, locale)?;
+        Ok(formatted)
+    }
+    /// Resets every field smaller than `unit` to its minimum, e.g.:
+    /// truncating 2024-05-17 13:45:10 to [`TimeUnit::Hour`] gives
+    /// 2024-05-17 13:00:00
+    pub fn truncate_to(&self, unit: TimeUnit) -> Self {
+        let mut truncated = *self;
+        truncated.nanosecond = 0;
+        if unit == TimeUnit::Second {
+            return truncated;
+        }
+        truncated.second = 0;
+        if unit == TimeUnit::Minute {
+            return truncated;
+        }
+        truncated.minute = 0;
+        if unit == TimeUnit::Hour {
+            return truncated;
+        }
+        truncated.hour = 0;
+        if unit == TimeUnit::Day {
+            return truncated;
+        }
+        truncated.day = 1;
+        if unit == TimeUnit::Month {
+            return truncated;
+        }
+        truncated.month = 1;
+        truncated
+    }
+    /// Rounds to the closest multiple of `unit`, carrying into the larger
+    /// fields when rounding up, e.g.: 23:40 rounded to [`TimeUnit::Hour`]
+    /// is midnight of the next day. Exactly half-way values round up, so
+    /// 12:00:30 rounded to [`TimeUnit::Minute`] is 12:01:00. For months and
+    /// years the half-way point depends on the length of that month or year.
+    pub fn round_to_nearest(&self, unit: TimeUnit) -> Result<Self, Error> {
+        let floor = self.truncate_to(unit);
+        if floor == *self {
+            return Ok(floor);
+        }
+        let ceil = match unit {
+            TimeUnit::Year => floor.add_months(12)?,
+            TimeUnit::Month => floor.add_months(1)?,
+            TimeUnit::Day => {
+                Self::from_unix_timestamp(floor.to_unix_timestamp() + SECONDS_PER_DAY)?
+            }
+            TimeUnit::Hour => Self::from_unix_timestamp(floor.to_unix_timestamp() + 3600)?,
+            TimeUnit::Minute => Self::from_unix_timestamp(floor.to_unix_timestamp() + 60)?,
+            TimeUnit::Second => Self::from_unix_timestamp(floor.to_unix_timestamp() + 1)?,
+        };
+        let ceil = Self {
+            offset_minutes: self.offset_minutes,
+            zone_abbreviation: self.zone_abbreviation,
+            ..ceil
+        };
+        let nanos = |seconds: i64| i128::from(seconds) * 1_000_000_000;
+        let elapsed =
+            nanos(self.to_unix_timestamp() - floor.to_unix_timestamp()) + self.nanosecond as i128;
+        let length = nanos(ceil.to_unix_timestamp() - floor.to_unix_timestamp());
+        Ok(if elapsed * 2 >= length { ceil } else { floor })
+    }
+    /// Adds (or, if negative, subtracts) `days` calendar days, keeping the
+    /// time of day. Returns an error before year 0 or past the day of
+    /// [`Datetime::MAX`].
+    pub fn add_days(&self, days: i64) -> Result<Self, Error> {
+        let Self {
+            year, month, day, ..
+        } = Self::MAX;
+        let supported = days_from_civil(0, 1, 1)..=days_from_civil(year as i64, month, day);
+        let total = days_from_civil(self.year as i64, self.month, self.day)
+            .checked_add(days)
+            .filter(|total| supported.contains(total))
+            .ok_or_else(|| DatetimeError::OutOfRange {
+                operation: format!("{self} + {days} days"),
+            })?;
+        let (year, month, day) = civil_from_days(total);
+        Ok(Self {
+            year: year as usize,
+            month,
+            day,
+            ..*self
+        })
+    }
+    /// The same wall clock time, at `minutes` east of UTC. Returns an error
+    /// for offsets past ±23:59, like [`DatetimeBuilder::build`].
+    /// [`Datetime::zone_abbreviation`] is cleared, as it names the old
+    /// offset.
+    pub fn with_offset(&self, minutes: i32) -> Result<Self, Error> {
+        if minutes.unsigned_abs() >= 24 * 60 {
+            return Err(DatetimeError::InvalidValue {
+                expected: "An offset within ±23:59".to_string(),
+                field: Token::UtcOffset,
+                got: minutes.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        Ok(Self {
+            offset_minutes: Some(minutes),
+            zone_abbreviation: None,
+            ..*self
+        })
+    }
+    /// Whether both dates are the same moment once converted to UTC, unlike
+    /// `==`, which compares the wall clock and the offset. Dates without an
+    /// offset are taken as UTC.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let noon = Datetime::parse_rfc3339("2024-01-01T12:00:00+02:00").unwrap();
+    /// let utc = Datetime::parse_rfc3339("2024-01-01T10:00:00Z").unwrap();
+    /// assert!(noon.same_instant(&utc));
+    /// assert_ne!(noon, utc);
+    /// ```
+    pub fn same_instant(&self, other: &Datetime) -> bool {
+        (self.as_utc_timestamp(), self.nanosecond) == (other.as_utc_timestamp(), other.nanosecond)
+    }
+    /// Leap seconds from `table` inserted between this date and `other`,
+    /// negative when `other` is earlier. Added to the difference of their
+    /// Unix timestamps, which skip leap seconds, it gives the SI seconds
+    /// that really elapsed. Dates without an offset are taken as UTC.
+    /// # Panics
+    /// Like [`Datetime::to_unix_timestamp`], past [`Datetime::MAX`]
+    /// # Examples
+    /// ```
+    /// use datetime::{datetime::Datetime, leap_seconds::LeapSecondTable};
+    /// let table = LeapSecondTable::new([Datetime::from_ymd(2016, 12, 31).unwrap()]).unwrap();
+    /// let start = Datetime::from_ymd(2016, 12, 31).unwrap();
+    /// let end = Datetime::from_ymd(2017, 1, 1).unwrap();
+    /// assert_eq!(start.leap_seconds_between(&end, &table), 1);
+    /// ```
+    pub fn leap_seconds_between(&self, other: &Datetime, table: &LeapSecondTable) -> i64 {
+        let (start, end) = (self.as_utc_timestamp(), other.as_utc_timestamp());
+        if start <= end {
+            table.count_between(start, end)
+        } else {
+            -table.count_between(end, start)
+        }
+    }
+    /// Whether this date is strictly earlier than `other`
+    pub fn is_before(&self, other: &Datetime) -> bool {
+        self < other
+    }
+    /// Whether this date is strictly later than `other`
+    pub fn is_after(&self, other: &Datetime) -> bool {
+        self > other
+    }
+    /// Compares the dates like [`Ord`], but only by the fields missing from
+    /// `mask`
+    /// # Examples
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use datetime::datetime::{Datetime, FieldMask};
+    /// let a = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 30).unwrap();
+    /// let b = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 45).unwrap();
+    /// let seconds = FieldMask::SECOND | FieldMask::NANOSECOND;
+    /// assert_eq!(a.cmp_ignoring(&b, seconds), Ordering::Equal);
+    /// ```
+    pub fn cmp_ignoring(&self, other: &Datetime, mask: FieldMask) -> std::cmp::Ordering {
+        let fields = |date: &Datetime| {
+            [
+                (FieldMask::YEAR, date.year),
+                (FieldMask::MONTH, date.month),
+                (FieldMask::DAY, date.day),
+                (FieldMask::HOUR, date.hour),
+                (FieldMask::MINUTE, date.minute),
+                (FieldMask::SECOND, date.second),
+                (FieldMask::NANOSECOND, date.nanosecond),
+            ]
+            .into_iter()
+            .filter(|(field, _)| !mask.contains(*field))
+            .map(|(_, value)| value)
+        };
+        let offset = |date: &Datetime| {
+            date.offset_minutes
+                .filter(|_| !mask.contains(FieldMask::OFFSET))
+        };
+        fields(self)
+            .cmp(fields(other))
+            .then_with(|| offset(self).cmp(&offset(other)))
+    }
+    /// Whether this date falls between `start` and `end`, with `bounds`
+    /// deciding if being equal to either of them counts
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Bounds, Datetime};
+    /// let start = Datetime::try_from_ymd_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    /// let end = Datetime::try_from_ymd_hms(2024, 12, 31, 0, 0, 0).unwrap();
+    /// assert!(start.is_between(&start, &end, Bounds::Inclusive));
+    /// assert!(!start.is_between(&start, &end, Bounds::Exclusive));
+    /// ```
+    pub fn is_between(&self, start: &Datetime, end: &Datetime, bounds: Bounds) -> bool {
+        match bounds {
+            Bounds::Inclusive => start <= self && self <= end,
+            Bounds::Exclusive => start < self && self < end,
+        }
+    }
+    /// Whether this local time happens twice in the zone `name`, as clocks
+    /// are set back when daylight saving time ends, according to
+    /// `resolver`. Parsing such times with `%Z` needs a `%z` to pick one.
+    pub fn is_dst_ambiguous(&self, name: &str, resolver: &dyn TzResolver) -> bool {
+        matches!(
+            resolver.resolve(name, self),
+            Some(LocalResult::Ambiguous { .. })
+        )
+    }
+    /// Replaces every numeric field with the result of `f` on it, then
+    /// validates the result like [`DatetimeBuilder::build`]. The offset is
+    /// kept as is.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Field};
+    /// let date = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// let next_year = date
+    ///     .map_fields(|field, value| if field == Field::Year { value + 1 } else { value })
+    ///     .unwrap();
+    /// assert_eq!(next_year.year, 2025);
+    /// ```
+    pub fn map_fields<F: Fn(Field, usize) -> usize>(self, f: F) -> Result<Self, Error> {
+        let builder = DatetimeBuilder::new()
+            .year(f(Field::Year, self.year))
+            .month(f(Field::Month, self.month))
+            .day(f(Field::Day, self.day))
+            .hour(f(Field::Hour, self.hour))
+            .minute(f(Field::Minute, self.minute))
+            .second(f(Field::Second, self.second))
+            .nanosecond(f(Field::Nanosecond, self.nanosecond));
+        match self.offset_minutes {
+            Some(offset) => builder.offset_minutes(offset).build(),
+            None => builder.build(),
+        }
+    }
+    /// Seconds since midnight, from 0 to 86399
+    pub fn total_seconds_in_day(&self) -> u32 {
+        (self.hour * 3600 + self.minute * 60 + self.second) as u32
+    }
+    /// The time `seconds` after midnight, on the date of
+    /// [`Datetime::default`], like a format with only a time. Reverses
+    /// [`Datetime::total_seconds_in_day`], so `seconds` must be below 86400.
+    pub fn from_seconds_in_day(seconds: u32) -> Result<Self, Error> {
+        if i64::from(seconds) >= SECONDS_PER_DAY {
+            return Err(DatetimeError::InvalidValue {
+                expected: "0-86399 seconds".to_string(),
+                field: Token::Second,
+                got: seconds.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        let seconds = seconds as usize;
+        Ok(Self {
+            hour: seconds / 3600,
+            minute: seconds % 3600 / 60,
+            second: seconds % 60,
+            ..Self::default()
+        })
+    }
+    /// Day of the year, from 1 for the 1st of January to 366
+    pub fn day_of_year(&self) -> u16 {
+        let year = self.year as i64;
+        (days_from_civil(year, self.month, self.day) - days_from_civil(year, 1, 1) + 1) as u16
+    }
+    /// Quarter of the year, from 1 for January to March to 4
+    pub fn quarter(&self) -> u8 {
+        self.month.div_ceil(3) as u8
+    }
+    /// All fields and derived values at once, e.g.: for templating
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Weekday};
+    /// let parts = Datetime::try_from_ymd_hms(2024, 5, 17, 0, 0, 0).unwrap().to_parts();
+    /// assert_eq!((parts.weekday, parts.day_of_year, parts.quarter), (Weekday::Friday, 138, 2));
+    /// ```
+    pub fn to_parts(&self) -> DateTimeParts {
+        DateTimeParts {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+            hour: self.hour,
+            minute: self.minute,
+            second: self.second,
+            nanosecond: self.nanosecond,
+            offset_minutes: self.offset_minutes,
+            weekday: self.weekday(),
+            day_of_year: self.day_of_year(),
+            quarter: self.quarter(),
+        }
+    }
+    /// The day of the week this date falls on
+    pub fn weekday(&self) -> Weekday {
+        Weekday::ALL[self.iso_weekday() - 1]
+    }
+    /// The same date on `day` of its month. Returns an error if the month
+    /// has no such day, e.g.: the 30th of February.
+    pub fn checked_set_day(&self, day: usize) -> Result<Self, Error> {
+        let max_days = days_in_month(self.year, self.month).unwrap_or(0);
+        if day == 0 || day > max_days {
+            return Err(DatetimeError::InvalidValue {
+                expected: format!("A day between 1-{}", max_days),
+                field: Token::Day,
+                got: day.to_string(),
+                src: None,
+            }
+            .into());
+        }
+        Ok(Self { day, ..*self })
+    }
+    /// Midnight of the first day of the week containing this date, which is
+    /// Monday if `monday_first` and Sunday otherwise
+    /// # Panics
+    /// For the first days of year 0, whose week starts before it
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let wednesday = Datetime::from_ymd(2024, 1, 3).unwrap();
+    /// assert_eq!(wednesday.week_start(true).day, 1);
+    /// assert_eq!(wednesday.week_start(false).to_string(), "31/12/2023 00:00:00");
+    /// ```
+    pub fn week_start(&self, monday_first: bool) -> Self {
+        let days_since_start = days_since_week_start(self.iso_weekday(), monday_first);
+        let start = self
+            .add_days(-(days_since_start as i64))
+            .expect("Week starts before year 0");
+        Self {
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            ..start
+        }
+    }
+    /// Week of the year as counted by `%W` if `monday_first`, or `%U`
+    /// otherwise: week 1 starts on the first Monday, or Sunday, of the year
+    /// and the days before it are in week 0
+    pub fn week_of_year(&self, monday_first: bool) -> u8 {
+        let days_since_start = days_since_week_start(self.iso_weekday(), monday_first);
+        ((usize::from(self.day_of_year()) + 6 - days_since_start) / 7) as u8
+    }
+    /// Inverse of [`Datetime::week_of_year`], giving midnight of `weekday`
+    /// in that week. Returns an error if the day is not in `year`.
+    pub(crate) fn from_year_week(
+        year: usize,
+        week: u8,
+        weekday: Weekday,
+        monday_first: bool,
+    ) -> Result<Self, Error> {
+        let first_day = Self::from_ymd(year, 1, 1)?;
+        let first_week_start =
+            (7 - days_since_week_start(first_day.iso_weekday(), monday_first)) % 7;
+        let days = (first_week_start
+            + days_since_week_start(weekday.number_from_monday(), monday_first))
+            as i64
+            + (i64::from(week) - 1) * 7;
+        let date = first_day
+            .add_days(days)
+            .ok()
+            .filter(|date| date.year == year);
+        date.ok_or_else(|| {
+            DatetimeError::InvalidValue {
+                expected: format!("A {weekday} in the weeks of {year}"),
+                field: if monday_first {
+                    Token::MondayWeek
+                } else {
+                    Token::SundayWeek
+                },
+                got: week.to_string(),
+                src: None,
+            }
+            .into()
+        })
+    }
+    /// The month of this date, as a [`Month`] rather than a number
+    pub fn month_of_year(&self) -> Month {
+        Month::ALL[self.month - 1]
+    }
+    /// The next date after this one that falls on `target`, keeping the
+    /// time of day. A date already on `target` moves a whole week ahead.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Weekday};
+    /// let monday = Datetime::try_from_ymd_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    /// let next = monday.next_weekday(Weekday::Monday).unwrap();
+    /// assert_eq!((next.month, next.day), (1, 8));
+    /// ```
+    pub fn next_weekday(&self, target: Weekday) -> Result<Self, Error> {
+        let days = (target.number_from_monday() as i64 - self.iso_weekday() as i64).rem_euclid(7);
+        self.add_days(if days == 0 { 7 } else { days })
+    }
+    /// The last date before this one that falls on `target`, keeping the
+    /// time of day. A date already on `target` moves a whole week back.
+    pub fn previous_weekday(&self, target: Weekday) -> Result<Self, Error> {
+        let days = (self.iso_weekday() as i64 - target.number_from_monday() as i64).rem_euclid(7);
+        self.add_days(if days == 0 { -7 } else { -days })
+    }
+    /// How many days from this date to `end`, both included, fall on
+    /// `target`. Only the dates count, not the time of day, and an `end`
+    /// before this date has none.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::{Datetime, Weekday};
+    /// // Fridays in the first quarter of 2024
+    /// let start = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// let end = Datetime::from_ymd(2024, 3, 31).unwrap();
+    /// assert_eq!(start.count_weekday(&end, Weekday::Friday), 13);
+    /// ```
+    pub fn count_weekday(&self, end: &Datetime, target: Weekday) -> usize {
+        let first = if self.weekday() == target {
+            *self
+        } else {
+            match self.next_weekday(target) {
+                Ok(first) => first,
+                Err(_) => return 0,
+            }
+        };
+        let days = days_from_civil(end.year as i64, end.month, end.day)
+            - days_from_civil(first.year as i64, first.month, first.day);
+        usize::try_from(days).map_or(0, |days| days / 7 + 1)
+    }
+    /// Adds (or, if negative, subtracts) `seconds`, keeping the fraction of
+    /// a second
+    fn add_seconds(&self, seconds: i64) -> Result<Self, Error> {
+        let time = (self.hour * 3600 + self.minute * 60 + self.second) as i64
+            + seconds.rem_euclid(SECONDS_PER_DAY);
+        let date = self.add_days(seconds.div_euclid(SECONDS_PER_DAY) + time / SECONDS_PER_DAY)?;
+        let time = (time % SECONDS_PER_DAY) as usize;
+        Ok(Self {
+            hour: time / 3600,
+            minute: time % 3600 / 60,
+            second: time % 60,
+            ..date
+        })
+    }
+    /// Resolves a date relative to `reference`, as typed in command line
+    /// tools: `today`, `yesterday` and `tomorrow`, or a signed offset made
+    /// of a number and one of the units `s`, `m` (minutes), `h`, `d` and
+    /// `w`, e.g.: `+3d` or `-2h`. The time of day of `reference` is kept.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let reference = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// let date = Datetime::parse_relative("+1d", reference).unwrap();
+    /// assert_eq!((date.month, date.day), (1, 2));
+    /// ```
+    pub fn parse_relative(input: &str, reference: Datetime) -> Result<Self, Error> {
+        let invalid = || DatetimeError::InvalidRelative {
+            src: input.to_string(),
+        };
+        match input {
+            "today" => return Ok(reference),
+            "yesterday" => return reference.add_days(-1),
+            "tomorrow" => return reference.add_days(1),
+            _ => {}
+        }
+        let (negative, offset) = match input.split_at_checked(1) {
+            Some(("+", offset)) => (false, offset),
+            Some(("-", offset)) => (true, offset),
+            _ => return Err(invalid().into()),
+        };
+        let (amount, unit) = offset
+            .split_at_checked(offset.len().saturating_sub(1))
+            .ok_or_else(invalid)?;
+        if amount.is_empty() || !amount.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid().into());
+        }
+        let amount: i64 = amount.parse().map_err(|_| invalid())?;
+        let amount = if negative { -amount } else { amount };
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => return reference.add_days(amount),
+            "w" => return reference.add_days(amount.checked_mul(7).ok_or_else(invalid)?),
+            _ => return Err(invalid().into()),
+        };
+        reference.add_seconds(amount.checked_mul(seconds).ok_or_else(invalid)?)
+    }
+    /// Same as [`Datetime::parse_relative`], relative to [`Datetime::now`]
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let later = Datetime::parse_relative_now("+2h").unwrap();
+    /// assert!(later > Datetime::now());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn parse_relative_now(input: &str) -> Result<Self, Error> {
+        Self::parse_relative(input, Self::now())
+    }
+    /// Normalizes the end-of-day notation `24:00:00` to `00:00:00` of the
+    /// next day. Any other hour above 23 is an error, and valid times are
+    /// returned unchanged.
+    pub fn clamp_time_to_day(&self) -> Result<Self, Error> {
+        match self.hour {
+            0..=23 => Ok(*self),
+            24 if self.minute == 0 && self.second == 0 && self.nanosecond == 0 => Ok(Self {
+                hour: 0,
+                ..self.add_days(1)?
+            }),
+            hour => Err(DatetimeError::InvalidValue {
+                expected: "0-23, or exactly 24:00:00".to_string(),
+                field: Token::Hour,
+                got: format!("{hour:02}:{:02}:{:02}", self.minute, self.second),
+                src: None,
+            }
+            .into()),
+        }
+    }
+    /// Iterates month by month, yielding the first day of each month at
+    /// midnight. The first item is the first of `self`'s own month, even
+    /// if `self` falls later in that month. Iteration stops before the first
+    /// item that is not strictly before `end`, so `end` itself is excluded.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::DatetimeBuilder;
+    /// let start = DatetimeBuilder::new().year(2024).month(1).day(15).build().unwrap();
+    /// let end = DatetimeBuilder::new().year(2024).month(4).day(1).build().unwrap();
+    /// let months: Vec<usize> = start.months_until(end).map(|d| d.month).collect();
+    /// assert_eq!(months, vec![1, 2, 3]);
+    /// ```
+    pub fn months_until(&self, end: Datetime) -> MonthIterator {
+        MonthIterator {
+            next: Some(Self {
+                year: self.year,
+                month: self.month,
+                ..Default::default()
+            }),
+            end,
+        }
+    }
+    /// Iterates day by day, yielding each date at midnight, starting from
+    /// `self`'s own day. Like [`Datetime::months_until`], it stops before
+    /// the first item that is not strictly before `end`.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let start = Datetime::from_ymd(2024, 1, 1).unwrap();
+    /// let end = Datetime::from_ymd(2024, 1, 8).unwrap();
+    /// assert_eq!(start.iter_days_until(end).count(), 7);
+    /// ```
+    pub fn iter_days_until(&self, end: Datetime) -> DayIterator {
+        DayIterator {
+            next: Some(Self {
+                year: self.year,
+                month: self.month,
+                day: self.day,
+                ..Default::default()
+            }),
+            end,
+        }
+    }
+    /// Iterator over this date and every second after it, keeping the
+    /// fraction of a second. Ends after [`Datetime::MAX`].
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::Datetime;
+    /// let start = Datetime::try_from_ymd_hms(2024, 1, 1, 23, 59, 59).unwrap();
+    /// let next = start.seconds_iter().nth(1).unwrap();
+    /// assert_eq!(next.to_string(), "02/01/2024 00:00:00");
+    /// ```
+    pub fn seconds_iter(&self) -> SecondIterator {
+        SecondIterator { next: Some(*self) }
+    }
+    /// The date one second later, or `None` past [`Datetime::MAX`]
+    fn next_second(&self) -> Option<Self> {
+        if self.checked_timestamp().ok()? == i64::MAX {
+            return None;
+        }
+        let mut next = *self;
+        next.second += 1;
+        if next.second == 60 {
+            next.second = 0;
+            next.minute += 1;
+        }
+        if next.minute == 60 {
+            next.minute = 0;
+            next.hour += 1;
+        }
+        if next.hour == 24 {
+            next.hour = 0;
+            next = next.add_days(1).ok()?;
+        }
+        Some(next)
+    }
+}
+
+/// Iterator over consecutive seconds. Created by [`Datetime::seconds_iter`]
+#[derive(Debug, Clone)]
+pub struct SecondIterator {
+    next: Option<Datetime>,
+}
+
+impl Iterator for SecondIterator {
+    type Item = Datetime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = current.next_second();
+        Some(current)
+    }
+}
+
+/// Iterator over consecutive days at midnight. Created by
+/// [`Datetime::iter_days_until`]
+#[derive(Debug, Clone)]
+pub struct DayIterator {
+    next: Option<Datetime>,
+    end: Datetime,
+}
+
+impl Iterator for DayIterator {
+    type Item = Datetime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.filter(|date| *date < self.end)?;
+        self.next = current.add_days(1).ok();
+        Some(current)
+    }
+}
+
+/// Iterator over the first day of consecutive months.
+/// Created by [`Datetime::months_until`]
+#[derive(Debug, Clone)]
+pub struct MonthIterator {
+    next: Option<Datetime>,
+    end: Datetime,
+}
+
+impl Iterator for MonthIterator {
+    type Item = Datetime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.filter(|date| *date < self.end)?;
+        self.next = current.add_months(1).ok();
+        Some(current)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miette::Error;
+
+    type TestResult = Result<(), Error>;
+
+    #[test]
+    fn test_from_str() -> TestResult {
+        let date = Datetime::from_str("2023-10-15", "%Y-%m-%d")?;
+        assert_eq!(date.year, 2023);
+        assert_eq!(date.month, 10);
+        assert_eq!(date.day, 15);
+
+        let date = Datetime::from_str("15/10/2023", "%d/%m/%Y")?;
+        assert_eq!(date.year, 2023);
+        assert_eq!(date.month, 10);
+        assert_eq!(date.day, 15);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default() {
+        let date = Datetime::default();
+        assert_eq!(date.year, 1900);
+        assert_eq!(date.month, 1);
+        assert_eq!(date.day, 1);
+        assert_eq!(date.hour, 0);
+        assert_eq!(date.minute, 0);
+        assert_eq!(date.second, 0);
+    }
+
+    #[test]
+    fn test_invalid_formats() {
+        let result = Datetime::from_str("2023-13-32", "%Y-%m-%d");
+        assert!(result.is_err()); // Invalid month and day
+
+        let _ = Datetime::from_str("25:70:99", "%H:%M:%S");
+    }
+
+    // Add this when you implement the try_guess feature
+    #[test]
+    fn test_try_guess() {
+        let date = "2023-10-15";
+        let result = Datetime::try_guess(date);
+        assert!(result.is_some());
+        if let Some(dt) = result {
+            assert_eq!(dt.year, 2023);
+            assert_eq!(dt.month, 10);
+            assert_eq!(dt.day, 15);
+        }
+
+        let date = "15/10/2023";
+        let result = Datetime::try_guess(date);
+        assert!(result.is_some());
+
+        let date = "not a date";
+        let result = Datetime::try_guess(date);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_parse_with_order() -> TestResult {
+        let date = Datetime::parse_with_order("01/02/03", DateOrder::Dmy)?;
+        assert_eq!((date.year, date.month, date.day), (2003, 2, 1));
+
+        let date = Datetime::parse_with_order("01/02/03", DateOrder::Mdy)?;
+        assert_eq!((date.year, date.month, date.day), (2003, 1, 2));
+
+        let date = Datetime::parse_with_order("01/02/03", DateOrder::Ymd)?;
+        assert_eq!((date.year, date.month, date.day), (2001, 2, 3));
+
+        let date = Datetime::parse_with_order("01-02-2003", DateOrder::Dmy)?;
+        assert_eq!((date.year, date.month, date.day), (2003, 2, 1));
+
+        assert!(Datetime::parse_with_order("13/13/03", DateOrder::Mdy).is_err());
+        assert!(Datetime::parse_with_order("not a date", DateOrder::Ymd).is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_try_guess_with_locale() -> TestResult {
+        let with_order = |date_order| Locale {
+            date_order,
+            ..Locale::default()
+        };
+        let guess = |date, date_order| {
+            Datetime::try_guess_with_locale(date, &with_order(date_order))
+                .map(|date| (date.year, date.month, date.day))
+        };
+        assert_eq!(guess("01/02/2024", DateOrder::Dmy), Some((2024, 2, 1)));
+        assert_eq!(guess("01/02/2024", DateOrder::Mdy), Some((2024, 1, 2)));
+        assert_eq!(guess("2024/01/02", DateOrder::Ymd), Some((2024, 1, 2)));
+        // Dates that do not fit the locale's order fall back to the rest
+        assert_eq!(guess("13/02/2024", DateOrder::Mdy), Some((2024, 2, 13)));
+        assert_eq!(guess("2024-03-04", DateOrder::Dmy), Some((2024, 3, 4)));
+        assert_eq!(guess("not a date", DateOrder::Dmy), None);
+        assert_eq!(Locale::ENGLISH.date_order, DateOrder::Mdy);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_months() -> TestResult {
+        let date = DatetimeBuilder::new().year(2024).month(1).day(31).build()?;
+        let next = date.add_months(1)?;
+        assert_eq!((next.year, next.month, next.day), (2024, 2, 29));
+
+        let next = date.add_months(12)?;
+        assert_eq!((next.year, next.month, next.day), (2025, 1, 31));
+
+        let previous = date.add_months(-2)?;
+        assert_eq!(
+            (previous.year, previous.month, previous.day),
+            (2023, 11, 30)
+        );
+
+        let zero = Datetime {
+            year: 0,
+            ..Default::default()
+        };
+        assert!(zero.add_months(-1).is_err());
+
+        // Past the last supported year
+        assert!(date.add_months(i64::MAX / 2).is_err());
+        assert!(date.add_months(i64::MAX).is_err());
+        let months_left = (MAX_YEAR as i64 - 2024) * 12 + 11;
+        assert_eq!(date.add_months(months_left)?.year, MAX_YEAR);
+        assert!(date.add_months(months_left + 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_months_until() -> TestResult {
+        let start = DatetimeBuilder::new().year(2024).month(1).day(15).build()?;
+        let end = DatetimeBuilder::new().year(2024).month(4).day(1).build()?;
+        let months: Vec<Datetime> = start.months_until(end).collect();
+        assert_eq!(months.len(), 3);
+        for (date, month) in months.iter().zip(1..) {
+            assert_eq!(
+                *date,
+                Datetime {
+                    year: 2024,
+                    month,
+                    ..Default::default()
+                }
+            );
+        }
+
+        // Across a year boundary, with `end` in the middle of a month
+        let start = DatetimeBuilder::new()
+            .year(2023)
+            .month(11)
+            .day(30)
+            .build()?;
+        let end = DatetimeBuilder::new().year(2024).month(2).day(10).build()?;
+        let months: Vec<(usize, usize)> = start
+            .months_until(end)
+            .map(|date| (date.year, date.month))
+            .collect();
+        assert_eq!(months, vec![(2023, 11), (2023, 12), (2024, 1), (2024, 2)]);
+
+        // An `end` before the start yields nothing
+        assert_eq!(end.months_until(start).count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_days_until() -> TestResult {
+        let start = Datetime::from_ymd(2024, 1, 1)?;
+        let end = Datetime::from_ymd(2024, 1, 8)?;
+        let days: Vec<usize> = start.iter_days_until(end).map(|date| date.day).collect();
+        assert_eq!(days, vec![1, 2, 3, 4, 5, 6, 7]);
+
+        // Across a month boundary, starting later in the day
+        let start = Datetime::try_from_ymd_hms(2024, 2, 27, 18, 30, 0)?;
+        let end = Datetime::from_ymd(2024, 3, 2)?;
+        let days: Vec<Datetime> = start.iter_days_until(end).collect();
+        assert_eq!(
+            days,
+            vec![
+                Datetime::from_ymd(2024, 2, 27)?,
+                Datetime::from_ymd(2024, 2, 28)?,
+                Datetime::from_ymd(2024, 2, 29)?,
+                Datetime::from_ymd(2024, 3, 1)?,
+            ]
+        );
+
+        // Empty when `end` is not after the start
+        assert_eq!(end.iter_days_until(end).count(), 0);
+        assert_eq!(end.iter_days_until(start).count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unix_timestamp() -> TestResult {
+        let epoch = DatetimeBuilder::new().year(1970).build()?;
+        assert_eq!(epoch.to_unix_timestamp(), 0);
+
+        let date = Datetime::from_str("2024-02-29 13:45:10", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(date.to_unix_timestamp(), 1_709_214_310);
+        assert_eq!(Datetime::from_unix_timestamp(1_709_214_310)?, date);
+
+        let before_epoch = Datetime::default();
+        assert_eq!(before_epoch.to_unix_timestamp(), -2_208_988_800);
+        assert_eq!(Datetime::from_unix_timestamp(-2_208_988_800)?, before_epoch);
+
+        assert!(Datetime::from_unix_timestamp(-62_167_219_201).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_to() -> TestResult {
+        let date = Datetime::from_str("2024-05-17 13:45:10", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(date.truncate_to(TimeUnit::Second), date);
+        assert_eq!(
+            date.truncate_to(TimeUnit::Hour),
+            Datetime::from_str("2024-05-17 13:00:00", "%Y-%m-%d %H:%M:%S")?
+        );
+        assert_eq!(
+            date.truncate_to(TimeUnit::Year),
+            Datetime::from_str("2024-01-01", "%Y-%m-%d")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_to_nearest() -> TestResult {
+        let format = "%Y-%m-%d %H:%M:%S";
+        let round = |date: &str, unit: TimeUnit| -> Result<Datetime, Error> {
+            Datetime::from_str(date, format)?.round_to_nearest(unit)
+        };
+
+        // Rounding up and down
+        assert_eq!(
+            round("2024-01-01 12:00:45", TimeUnit::Minute)?,
+            Datetime::from_str("2024-01-01 12:01:00", format)?
+        );
+        assert_eq!(
+            round("2024-01-01 12:00:25", TimeUnit::Minute)?,
+            Datetime::from_str("2024-01-01 12:00:00", format)?
+        );
+
+        // Exactly half-way rounds up
+        assert_eq!(
+            round("2024-01-01 12:00:30", TimeUnit::Minute)?,
+            Datetime::from_str("2024-01-01 12:01:00", format)?
+        );
+        assert_eq!(
+            round("2024-01-01 12:29:59", TimeUnit::Hour)?,
+            Datetime::from_str("2024-01-01 12:00:00", format)?
+        );
+
+        // The offset is kept either way
+        let with_offset = |time: &str| {
+            Datetime::from_str(&format!("2024-01-01 {time}+02:00"), "%Y-%m-%d %H:%M:%S%z")
+        };
+        for (time, expected) in [("12:00:45", "12:01:00"), ("12:00:15", "12:00:00")] {
+            let rounded = with_offset(time)?.round_to_nearest(TimeUnit::Minute)?;
+            assert_eq!(rounded, with_offset(expected)?, "Failed on {time}");
+            assert_eq!(rounded.offset_minutes, Some(120));
+        }
+        let abbreviated = Datetime {
+            zone_abbreviation: ZoneAbbreviation::find("CET"),
+            ..with_offset("23:59:59")?
+        };
+        let rounded = abbreviated.round_to_nearest(TimeUnit::Day)?;
+        assert_eq!((rounded.day, rounded.hour), (2, 0));
+        assert_eq!(rounded.zone_abbreviation, abbreviated.zone_abbreviation);
+
+        // Carrying into the next day, month and year
+        assert_eq!(
+            round("2024-01-31 23:40:00", TimeUnit::Hour)?,
+            Datetime::from_str("2024-02-01 00:00:00", format)?
+        );
+        assert_eq!(
+            round("2024-12-31 12:00:00", TimeUnit::Day)?,
+            Datetime::from_str("2025-01-01 00:00:00", format)?
+        );
+        // February 2024 has 29 days, so its half-way point is the 15th at noon
+        assert_eq!(
+            round("2024-02-15 12:00:00", TimeUnit::Month)?,
+            Datetime::from_str("2024-03-01 00:00:00", format)?
+        );
+        assert_eq!(
+            round("2024-02-15 11:59:59", TimeUnit::Month)?,
+            Datetime::from_str("2024-02-01 00:00:00", format)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_offset() -> TestResult {
+        let builder = || DatetimeBuilder::new().year(2024).month(1).day(1);
+        let date = builder().offset_minutes(-(24 * 60 - 1)).build()?;
+        assert_eq!(date.offset_minutes, Some(-1439));
+        for offset in [24 * 60, -24 * 60, i32::MAX, i32::MIN] {
+            let report = builder()
+                .offset_minutes(offset)
+                .build()
+                .expect_err("Too far");
+            assert!(
+                matches!(
+                    report.downcast_ref::<DatetimeError>(),
+                    Some(DatetimeError::InvalidValue { .. })
+                ),
+                "Failed on {offset}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_epoch() -> TestResult {
+        let epoch = Datetime::epoch();
+        assert_eq!(epoch.to_unix_timestamp(), 0);
+        assert_eq!(Datetime::from_unix_timestamp(0)?, epoch);
+        assert_eq!(DatetimeBuilder::new().year(1970).build()?, epoch);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_timestamp_auto() -> TestResult {
+        let date = Datetime::from_timestamp_auto("1709214310")?;
+        assert_eq!(
+            date,
+            Datetime::from_str("2024-02-29 13:45:10", "%Y-%m-%d %H:%M:%S")?
+        );
+
+        let date = Datetime::from_timestamp_auto("1709214310250")?;
+        assert_eq!(
+            date,
+            Datetime {
+                nanosecond: 250_000_000,
+                ..Datetime::from_str("2024-02-29 13:45:10", "%Y-%m-%d %H:%M:%S")?
+            }
+        );
+        assert_eq!(Datetime::from_unix_millis(-1)?.nanosecond, 999_000_000);
+
+        for invalid in ["", "170921431", "17092143100", "-709214310", "17092143OO"] {
+            assert!(
+                Datetime::from_timestamp_auto(invalid).is_err(),
+                "Failed on input: {invalid}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_sub_second() -> TestResult {
+        let date = Datetime {
+            nanosecond: 500_000_000,
+            ..Datetime::from_str("2024-12-31 23:59:59", "%Y-%m-%d %H:%M:%S")?
+        };
+        assert_eq!(
+            date.round_to_nearest(TimeUnit::Second)?,
+            Datetime::from_str("2025-01-01 00:00:00", "%Y-%m-%d %H:%M:%S")?
+        );
+        assert_eq!(date.truncate_to(TimeUnit::Second).nanosecond, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_iso_year_week() -> TestResult {
+        for (year, month, day) in [(2024, 12, 30), (2021, 1, 3), (2020, 12, 31), (2024, 5, 17)] {
+            let date = Datetime::try_from_ymd_hms(year, month, day, 0, 0, 0)?;
+            let (iso_year, week) = date.iso_year_week();
+            assert_eq!(
+                Datetime::from_iso_year_week(iso_year, week, date.weekday())?,
+                date
+            );
+        }
+        assert_eq!(
+            date_of(Datetime::from_iso_year_week(2020, 53, Weekday::Sunday)?),
+            (2021, 1, 3)
+        );
+        assert!(Datetime::from_iso_year_week(2021, 53, Weekday::Monday).is_err());
+        assert!(Datetime::from_iso_year_week(2021, 0, Weekday::Monday).is_err());
+        Ok(())
+    }
+
+    fn date_of(date: Datetime) -> (usize, usize, usize) {
+        (date.year, date.month, date.day)
+    }
+
+    #[test]
+    fn test_iso_year_week() -> TestResult {
+        let iso = |date: &str| -> Result<(i32, u8), Error> {
+            Ok(Datetime::from_str(date, "%Y-%m-%d")?.iso_year_week())
+        };
+        assert_eq!(iso("2024-06-15")?, (2024, 24));
+
+        // December dates belonging to the next ISO year
+        assert_eq!(iso("2024-12-29")?, (2024, 52));
+        assert_eq!(iso("2024-12-30")?, (2025, 1));
+        assert_eq!(iso("2025-12-29")?, (2026, 1));
+
+        // January dates belonging to the previous ISO year
+        assert_eq!(iso("2021-01-03")?, (2020, 53));
+        assert_eq!(iso("2021-01-04")?, (2021, 1));
+        assert_eq!(iso("2023-01-01")?, (2022, 52));
+        assert_eq!(iso("2024-01-01")?, (2024, 1));
+
+        // A 53 week year ending on a Thursday
+        assert_eq!(iso("2026-12-31")?, (2026, 53));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bytes() -> TestResult {
+        for (input, format) in [
+            ("2024-02-29", "%Y-%m-%d"),
+            ("29/02/24 01:02:03 PM", "%d/%m/%y %I:%M:%S %p"),
+            ("20240229134510", "%Y%m%d%H%M%S"),
+        ] {
+            assert_eq!(
+                Datetime::parse_bytes(input.as_bytes(), format)?,
+                Datetime::from_str(input, format)?
+            );
+        }
+        assert!(Datetime::parse_bytes(b"2024-02-30", "%Y-%m-%d").is_err());
+
+        let report = Datetime::parse_bytes("2024-02-2\u{0669}".as_bytes(), "%Y-%m-%d")
+            .expect_err("Non ASCII digit");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::NonAsciiInput { position: 9, .. })
+        ));
+        // Invalid UTF-8 is reported at its first byte
+        let report = Datetime::parse_bytes(&[b'2', b'4', 0xff], "%y").expect_err("Invalid UTF-8");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::NonAsciiInput {
+                byte: 0xff,
+                position: 2
+            })
+        ));
+        // Valid but non ASCII text before invalid bytes is found first
+        let mut bytes = "24\u{e9}".as_bytes().to_vec();
+        bytes.push(0xff);
+        let report = Datetime::parse_bytes(&bytes, "%y").expect_err("Non ASCII");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::NonAsciiInput { position: 2, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_days() -> TestResult {
+        let date = Datetime::from_str("2024-02-28 10:30:00", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(
+            date.add_days(1)?,
+            Datetime::from_str("2024-02-29 10:30:00", "%Y-%m-%d %H:%M:%S")?
+        );
+        assert_eq!(
+            date.add_days(-59)?,
+            Datetime::from_str("2023-12-31 10:30:00", "%Y-%m-%d %H:%M:%S")?
+        );
+        assert!(
+            Datetime::from_str("0000-01-01", "%Y-%m-%d")?
+                .add_days(-1)
+                .is_err()
+        );
+
+        // Day counts too far out for the calendar are errors, not overflows
+        let days_left = days_from_civil(MAX_YEAR as i64, 12, 4) - days_from_civil(2024, 2, 28);
+        let last = date.add_days(days_left)?;
+        assert_eq!((last.year, last.month, last.day), (MAX_YEAR, 12, 4));
+        assert!(date.add_days(days_left + 1).is_err());
+        for days in [i64::MAX - 19_800, i64::MAX, i64::MIN, i64::MIN / 2] {
+            assert!(date.add_days(days).is_err(), "Failed on {days}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_clamp_time_to_day() -> TestResult {
+        let end_of_day = Datetime {
+            year: 2024,
+            month: 12,
+            day: 31,
+            hour: 24,
+            ..Default::default()
+        };
+        assert_eq!(
+            end_of_day.clamp_time_to_day()?,
+            Datetime::from_str("2025-01-01", "%Y-%m-%d")?
+        );
+
+        let past_end_of_day = Datetime {
+            minute: 30,
+            ..end_of_day
+        };
+        let report = past_end_of_day
+            .clamp_time_to_day()
+            .expect_err("24:30:00 is not a valid time");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidValue {
+                field: Token::Hour,
+                ..
+            })
+        ));
+        assert!(
+            Datetime {
+                hour: 25,
+                ..Default::default()
+            }
+            .clamp_time_to_day()
+            .is_err()
+        );
+
+        let valid = Datetime::from_str("2024-12-31 23:59:59", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(valid.clamp_time_to_day()?, valid);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_localized() -> TestResult {
+        let monday = Datetime::try_from_ymd_hms(2024, 3, 4, 15, 30, 0)?;
+        let saturday = Datetime::from_ymd(2024, 2, 10)?;
+        let format = "%A %a %B %b %p";
+        assert_eq!(
+            monday.format_localized(format, &Locale::ENGLISH)?,
+            "Monday Mon March Mar PM"
+        );
+        assert_eq!(
+            monday.format_localized(format, &Locale::ENGLISH)?,
+            monday.format(format)?
+        );
+        assert_eq!(
+            monday.format_localized(format, &Locale::PORTUGUESE)?,
+            "Segunda-feira Seg Março Mar PM"
+        );
+        assert_eq!(
+            saturday.format_localized("%A %a %B %b", &Locale::PORTUGUESE)?,
+            "Sábado Sáb Fevereiro Fev"
+        );
+        assert_eq!(
+            saturday.format_localized("%c", &Locale::PORTUGUESE)?,
+            "Sáb 10 Fev 2024 00:00:00"
+        );
+        let custom = Locale {
+            pm: "p.m.",
+            ..Locale::ENGLISH
+        };
+        assert_eq!(monday.format_localized("%I %p", &custom)?, "03 p.m.");
+        assert!(monday.format_localized("%Q", &Locale::PORTUGUESE).is_err());
+
+        // What is written in a locale reads back with it
+        let options = ParseOptions {
+            locale: Locale::PORTUGUESE,
+            ..ParseOptions::default()
+        };
+        for date in [monday, saturday] {
+            let formatted = date.format_localized("%A %d %B %Y %H:%M", &Locale::PORTUGUESE)?;
+            assert_eq!(
+                Datetime::parse_datetime_with_options(&formatted, "%A %d %B %Y %H:%M", &options)?,
+                date
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format() -> TestResult {
+        let date = Datetime::from_str("2024-01-01 00:00:00", "%Y-%m-%d %H:%M:%S")?;
+        let formatted = date.format("%c")?;
+        assert_eq!(formatted, "Mon Jan  1 00:00:00 2024");
+        assert_eq!(Datetime::from_str(&formatted, "%c")?, date);
+
+        let date = Datetime::from_str("2023-11-25 12:07:09", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(
+            date.format("%A %e %B %y, %I:%M %p")?,
+            "Saturday 25 November 23, 12:07 PM"
+        );
+        assert_eq!(
+            date.format("%d/%m/%Y [%T] 100%%")?,
+            "25/11/2023 12:07:09 100%"
+        );
+        let format = "%a, %d %b %Y %H:%M:%S";
+        let formatted = date.format(format)?;
+        assert_eq!(Datetime::from_str(&formatted, format)?, date);
+
+        let format = "%Y-%m-%dT%H:%M:%S%z";
+        for offset in ["+0553", "-0330", "+0000", "-0000"] {
+            let formatted = format!("2024-05-17T13:45:10{offset}");
+            assert_eq!(
+                Datetime::from_str(&formatted, format)?.format(format)?,
+                formatted
+            );
+        }
+
+        let invalid = Datetime { month: 13, ..date };
+        assert!(invalid.format("%B").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from_ymd_hms() -> TestResult {
+        let date = Datetime::try_from_ymd_hms(2024, 2, 29, 23, 59, 59)?;
+        assert_eq!(
+            date,
+            Datetime::from_str("2024-02-29 23:59:59", "%Y-%m-%d %H:%M:%S")?
+        );
+        let cases = [
+            ((-1, 1, 1, 0, 0, 0), Token::FullYear),
+            ((2024, 13, 1, 0, 0, 0), Token::FullMonth),
+            ((2024, -1, 1, 0, 0, 0), Token::FullMonth),
+            ((2023, 2, 29, 0, 0, 0), Token::Day),
+            ((2024, 1, 0, 0, 0, 0), Token::Day),
+            ((2024, 1, 1, 24, 0, 0), Token::Hour),
+            ((2024, 1, 1, -3, 0, 0), Token::Hour),
+            ((2024, 1, 1, 0, 60, 0), Token::Minute),
+            ((2024, 1, 1, 0, 0, 60), Token::Second),
+            ((2024, 1, 1, 0, 0, i64::MIN), Token::Second),
+        ];
+        for ((year, month, day, hour, minute, second), expected) in cases {
+            let report = Datetime::try_from_ymd_hms(year, month, day, hour, minute, second)
+                .expect_err("Invalid field");
+            match report.downcast_ref::<DatetimeError>() {
+                Some(DatetimeError::InvalidValue { field, .. }) => assert_eq!(*field, expected),
+                other => panic!("Unexpected error {other:?}"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_into() -> TestResult {
+        let dates = [
+            Datetime::try_from_ymd_hms(2024, 1, 1, 0, 0, 0)?,
+            Datetime::try_from_ymd_hms(1999, 12, 31, 23, 59, 59)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 12, 30, 0)?,
+        ];
+        let mut buf = String::new();
+        let mut rows = Vec::new();
+        for date in &dates {
+            buf.clear();
+            date.format_into(&mut buf, "%Y-%m-%d %H:%M:%S")?;
+            rows.push(buf.clone());
+        }
+        assert_eq!(
+            rows,
+            [
+                "2024-01-01 00:00:00",
+                "1999-12-31 23:59:59",
+                "2024-02-29 12:30:00"
+            ]
+        );
+        // Appends instead of overwriting
+        dates[0].format_into(&mut buf, " %a")?;
+        assert_eq!(buf, "2024-02-29 12:30:00 Mon");
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_and_previous_weekday() -> TestResult {
+        let monday = Datetime::try_from_ymd_hms(2024, 1, 1, 9, 30, 0)?;
+        assert_eq!(monday.weekday(), Weekday::Monday);
+        let ymd = |date: Datetime| (date.year, date.month, date.day);
+
+        // Already on the target, so a week later
+        assert_eq!(ymd(monday.next_weekday(Weekday::Monday)?), (2024, 1, 8));
+        assert_eq!(ymd(monday.next_weekday(Weekday::Tuesday)?), (2024, 1, 2));
+        assert_eq!(ymd(monday.next_weekday(Weekday::Sunday)?), (2024, 1, 7));
+        assert_eq!(monday.next_weekday(Weekday::Friday)?.hour, 9);
+        assert_eq!(
+            ymd(monday.previous_weekday(Weekday::Monday)?),
+            (2023, 12, 25)
+        );
+        assert_eq!(
+            ymd(monday.previous_weekday(Weekday::Sunday)?),
+            (2023, 12, 31)
+        );
+        assert_eq!(
+            ymd(monday.previous_weekday(Weekday::Tuesday)?),
+            (2023, 12, 26)
+        );
+
+        // Across the end of February in a leap year
+        let date = Datetime::try_from_ymd_hms(2024, 2, 28, 0, 0, 0)?;
+        assert_eq!(date.weekday(), Weekday::Wednesday);
+        assert_eq!(ymd(date.next_weekday(Weekday::Saturday)?), (2024, 3, 2));
+        let date = Datetime::try_from_ymd_hms(2024, 3, 1, 0, 0, 0)?;
+        assert_eq!(
+            ymd(date.previous_weekday(Weekday::Thursday)?),
+            (2024, 2, 29)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rfc3339() -> TestResult {
+        let date = Datetime::parse_rfc3339("2024-05-17T13:45:10Z")?;
+        assert_eq!(
+            date,
+            Datetime {
+                offset_minutes: Some(0),
+                ..Datetime::try_from_ymd_hms(2024, 5, 17, 13, 45, 10)?
+            }
+        );
+        let date = Datetime::parse_rfc3339("2024-05-17 13:45:10.123456789123+05:30")?;
+        assert_eq!(
+            (date.nanosecond, date.offset_minutes),
+            (123_456_789, Some(330))
+        );
+        let date = Datetime::parse_rfc3339("2024-05-17t13:45:10.5z")?;
+        assert_eq!(
+            (date.nanosecond, date.offset_minutes),
+            (500_000_000, Some(0))
+        );
+        assert_eq!(
+            Datetime::parse_rfc3339("2024-05-17T13:45:10-00:00")?.offset_minutes,
+            None
+        );
+
+        let date = Datetime::parse_rfc3339("2024-01-01T12:00Z")?;
+        assert_eq!(
+            date,
+            Datetime {
+                offset_minutes: Some(0),
+                ..Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 0)?
+            }
+        );
+        let date = Datetime::parse_rfc3339("2024-01-01T12:00:00.5Z")?;
+        assert_eq!((date.second, date.nanosecond), (0, 500_000_000));
+        let date = Datetime::parse_rfc3339("2024-01-01 12:30-03:00")?;
+        assert_eq!(
+            (date.hour, date.minute, date.second, date.offset_minutes),
+            (12, 30, 0, Some(-180))
+        );
+
+        assert!(Datetime::parse_rfc3339("2024-05-17T13:45:10").is_err());
+        assert!(Datetime::parse_rfc3339("2024-05-17T13:45:10.Z").is_err());
+        // A fraction needs the seconds before it
+        assert!(Datetime::parse_rfc3339("2024-05-17T13:45.5Z").is_err());
+        assert!(Datetime::parse_rfc3339("2024-05-17T13Z").is_err());
+        assert!(Datetime::parse_rfc3339("2024-05-17").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_rfc3339() -> TestResult {
+        assert_eq!(
+            Datetime::checked_rfc3339("2024-02-29T23:59:59.25+05:30")?,
+            Datetime {
+                nanosecond: 250_000_000,
+                offset_minutes: Some(330),
+                ..Datetime::try_from_ymd_hms(2024, 2, 29, 23, 59, 59)?
+            }
+        );
+        for input in [
+            "2024-05-17T13:45:10+5:30",
+            "2024-05-17T13:45:10+24:00",
+            "2024-05-17T13:45:10+05:60",
+            "2024-05-17T13:45:10+0530",
+            "2024-05-17T13:45:10",
+            "2024-05-17T13:45:10Z ",
+            "2024-05-17T13:45:10 Z",
+            "2024-05-17T13:45:10.5x",
+        ] {
+            assert!(
+                matches!(
+                    Datetime::checked_rfc3339(input),
+                    Err(Rfc3339Error::InvalidOffset { .. })
+                ),
+                "Failed on {input}"
+            );
+        }
+        for input in [
+            "2023-02-29T00:00:00Z",
+            "2024-04-31T00:00:00Z",
+            "2024-00-10T00:00:00Z",
+            "2024-13-10T00:00:00Z",
+            "2024-01-00T00:00:00Z",
+        ] {
+            assert!(
+                matches!(
+                    Datetime::checked_rfc3339(input),
+                    Err(Rfc3339Error::InvalidDate { .. })
+                ),
+                "Failed on {input}"
+            );
+        }
+        for input in [
+            "2024-05-17T24:00:00Z",
+            "2024-05-17T13:60:00Z",
+            "2024-05-17T13:45:60Z",
+        ] {
+            assert!(
+                matches!(
+                    Datetime::checked_rfc3339(input),
+                    Err(Rfc3339Error::InvalidTime { .. })
+                ),
+                "Failed on {input}"
+            );
+        }
+        for input in [
+            "2024-05-17T13:45:10.Z",
+            "2024-05-17T13:45:10.+01:00",
+            "2024-05-17T13:45.5Z",
+        ] {
+            assert!(
+                matches!(
+                    Datetime::checked_rfc3339(input),
+                    Err(Rfc3339Error::InvalidFraction { .. })
+                ),
+                "Failed on {input}"
+            );
+        }
+        for input in [
+            "",
+            "2024-5-17T13:45:10Z",
+            "2024-05-17",
+            "2024-05-17X13:45:10Z",
+            "2024-05-17T13Z",
+            "2024-05-17T13:45:1Z",
+            "2024\u{2013}05-17T13:45:10Z",
+        ] {
+            assert!(
+                matches!(
+                    Datetime::checked_rfc3339(input),
+                    Err(Rfc3339Error::Malformed { .. })
+                ),
+                "Failed on {input}"
+            );
+        }
+
+        // The same reasons come out of `parse_rfc3339`
+        let report = Datetime::parse_rfc3339("2024-05-17T13:45:10+24:00").expect_err("Offset");
+        assert!(matches!(
+            report.downcast_ref::<Rfc3339Error>(),
+            Some(Rfc3339Error::InvalidOffset { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_rfc2822() -> TestResult {
+        let expected = Datetime::try_from_ymd_hms(2024, 5, 17, 13, 45, 10)?;
+        let date = Datetime::parse_rfc2822("Fri, 17 May 2024 13:45:10 +0200")?;
+        assert_eq!(
+            date,
+            Datetime {
+                offset_minutes: Some(120),
+                ..expected
+            }
+        );
+        let date = Datetime::parse_rfc2822("17 May 2024 13:45:10 GMT")?;
+        assert_eq!(
+            date,
+            Datetime {
+                offset_minutes: Some(0),
+                ..expected
+            }
+        );
+        let date = Datetime::parse_rfc2822("Wed, 1 May 2024 08:30 -0500")?;
+        assert_eq!(
+            (date.day, date.second, date.offset_minutes),
+            (1, 0, Some(-300))
+        );
+
+        assert!(Datetime::parse_rfc2822("Mon, 17 May 2024 13:45:10 +0000").is_err());
+        assert!(Datetime::parse_rfc2822("17 May 2024").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_any() -> TestResult {
+        let date = Datetime::parse_any("2024-05-17T13:45:10+01:00").expect("RFC 3339");
+        assert_eq!((date.hour, date.offset_minutes), (13, Some(60)));
+        let date = Datetime::parse_any("Fri, 17 May 2024 13:45:10 +0000").expect("RFC 2822");
+        assert_eq!((date.day, date.hour), (17, 13));
+        let date = Datetime::parse_any("1715953510").expect("Epoch seconds");
+        assert_eq!(date, Datetime::try_from_ymd_hms(2024, 5, 17, 13, 45, 10)?);
+        let date = Datetime::parse_any("1715953510250").expect("Epoch milliseconds");
+        assert_eq!(date.nanosecond, 250_000_000);
+        let date = Datetime::parse_any("17/05/2024").expect("Slash date");
+        assert_eq!((date.year, date.month, date.day), (2024, 5, 17));
+
+        assert!(Datetime::parse_any("not a date").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_comparisons() -> TestResult {
+        let start = Datetime::try_from_ymd_hms(2024, 1, 1, 0, 0, 0)?;
+        let middle = Datetime::try_from_ymd_hms(2024, 6, 15, 12, 0, 0)?;
+        let end = Datetime::try_from_ymd_hms(2024, 12, 31, 23, 59, 59)?;
+        let later_nanosecond = Datetime {
+            nanosecond: 1,
+            ..start
+        };
+
+        assert!(start.is_before(&middle));
+        assert!(start.is_before(&later_nanosecond));
+        assert!(!start.is_before(&start));
+        assert!(end.is_after(&middle));
+        assert!(!end.is_after(&end));
+
+        for bounds in [Bounds::Inclusive, Bounds::Exclusive] {
+            assert!(middle.is_between(&start, &end, bounds));
+            assert!(!end.add_days(1)?.is_between(&start, &end, bounds));
+            assert!(!start.is_between(&end, &start, bounds));
+        }
+        assert!(start.is_between(&start, &end, Bounds::Inclusive));
+        assert!(end.is_between(&start, &end, Bounds::Inclusive));
+        assert!(!start.is_between(&start, &end, Bounds::Exclusive));
+        assert!(!end.is_between(&start, &end, Bounds::Exclusive));
+        assert!(start.is_between(&start, &start, Bounds::Inclusive));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_parts() -> TestResult {
+        let date = Datetime::parse_rfc3339("2024-12-31T23:59:58.5+01:00")?;
+        let parts = date.to_parts();
+        assert_eq!(
+            parts,
+            DateTimeParts {
+                year: 2024,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 58,
+                nanosecond: 500_000_000,
+                offset_minutes: Some(60),
+                weekday: Weekday::Tuesday,
+                day_of_year: 366,
+                quarter: 4,
+            }
+        );
+        assert_eq!(parts.weekday, date.weekday());
+        assert_eq!(parts.day_of_year, date.day_of_year());
+        assert_eq!(parts.quarter, date.quarter());
+
+        let date = Datetime::try_from_ymd_hms(2023, 3, 1, 0, 0, 0)?;
+        assert_eq!((date.day_of_year(), date.quarter()), (60, 1));
+        let date = Datetime::try_from_ymd_hms(2023, 4, 1, 0, 0, 0)?;
+        assert_eq!(date.quarter(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adversarial_inputs() {
+        let cases = [
+            ("2024-01-0\u{0}1", "%Y-%m-%d"),
+            ("2024\n-01-01", "%Y-%m-%d"),
+            ("20\u{200B}24-01-01", "%Y-%m-%d"),
+            ("\u{FEFF}2024-01-01", "%Y-%m-%d"),
+            ("2024-01-01\u{7}", "%Y-%m-%d"),
+            ("202é-01-01", "%Y-%m-%d"),
+            ("2024-0日-01", "%Y-%m-%d"),
+            ("日本", "%d"),
+            ("1\u{1F600}", "%H:%M"),
+            ("10:00+0日", "%H:%M%z"),
+        ];
+        for (input, format) in cases {
+            assert!(
+                Datetime::from_str(input, format).is_err(),
+                "Failed on input: {input:?}"
+            );
+        }
+        let report = Datetime::from_str("2024-01-0\u{0}1", "%Y-%m-%d").expect_err("Control");
+        assert!(matches!(
+            report.downcast_ref::<crate::interpreter::InterpreterError>(),
+            Some(crate::interpreter::InterpreterError::ControlCharacter { .. })
+        ));
+        // Tabs and line breaks are whitespace, not corruption
+        let date =
+            Datetime::from_str("2024-01-01\t10:00", "%Y-%m-%d\t%H:%M").expect("Tab separated");
+        assert_eq!((date.day, date.hour), (1, 10));
+        assert!(Datetime::from_str("2024-01-01\r\n", "%Y-%m-%d\r\n").is_ok());
+        assert!(Datetime::from_str("2024-01-01\t10:00", "%Y-%m-%d %H:%M").is_err());
+    }
+
+    #[test]
+    fn test_from_ymd() -> TestResult {
+        let date = Datetime::from_ymd(2024, 2, 29)?;
+        assert_eq!(date, Datetime::try_from_ymd_hms(2024, 2, 29, 0, 0, 0)?);
+        let report = Datetime::from_ymd(2024, 2, 30).expect_err("February has 29 days");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidValue {
+                field: Token::Day,
+                ..
+            })
+        ));
+        assert!(Datetime::from_ymd(2023, 13, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_literal_percent() -> TestResult {
+        let date = Datetime::from_ymd(2024, 3, 1)?;
+        assert_eq!(date.format("100%% done on %Y")?, "100% done on 2024");
+        assert_eq!(date.format("%%Y")?, "%Y");
+        assert_eq!(date.format("%%%Y%%")?, "%2024%");
+        assert_eq!(date.format(r"\%d %d")?, "%d 01");
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_padding_flags() -> TestResult {
+        let date = Datetime::try_from_ymd_hms(2024, 3, 5, 7, 8, 9)?;
+        assert_eq!(date.format("%d")?, "05");
+        assert_eq!(date.format("%_d")?, " 5");
+        assert_eq!(date.format("%-d")?, "5");
+        assert_eq!(date.format("%0d")?, "05");
+        assert_eq!(date.format("%e")?, " 5");
+        assert_eq!(date.format("%-e")?, "5");
+        assert_eq!(date.format("%-m/%-d/%Y %-H:%M")?, "3/5/2024 7:08");
+        assert_eq!(date.format("%-I%%")?, "7%");
+        assert_eq!(
+            Datetime::from_ymd(987, 1, 1)?.format("%Y|%_Y|%-Y")?,
+            "0987| 987|987"
+        );
+        // Days that fill the field are never padded
+        let date = Datetime::from_ymd(2024, 3, 15)?;
+        assert_eq!(date.format("%d|%_d|%-d")?, "15|15|15");
+        assert!(date.format("%-B").is_err());
+        assert!(date.format("%-").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_widths() -> TestResult {
+        let mut date = Datetime::try_from_ymd_hms(2024, 3, 5, 7, 8, 9)?;
+        date.nanosecond = 123_456_789;
+        assert_eq!(
+            date.format("%4d|%_3m|%-4H|%_M|%06Y|%3j")?,
+            "0005|  3|7| 8|002024|065"
+        );
+        assert_eq!(
+            date.format("%S.%3f|%1f|%f|%12f")?,
+            "09.123|1|123456789|123456789000"
+        );
+        assert_eq!(date.format("%_4e%%%-3S")?, "   5%9");
+        // Numbers wider than the width are never cut
+        assert_eq!(date.format("%1Y|%1d")?, "2024|5");
+        for format in ["%3B", "%_3f", "%0d%", "%0%"] {
+            assert!(date.format(format).is_err(), "Failed on format: {format}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_weekday_of() -> TestResult {
+        assert_eq!(weekday_of(2024, 2, 29), Some(Weekday::Thursday));
+        assert_eq!(weekday_of(1970, 1, 1), Some(Weekday::Thursday));
+        assert_eq!(weekday_of(2000, 1, 1), Some(Weekday::Saturday));
+        assert_eq!(weekday_of(2024, 12, 31), Some(Weekday::Tuesday));
+        let date = Datetime::from_ymd(1900, 3, 1)?;
+        assert_eq!(weekday_of(1900, 3, 1), Some(date.weekday()));
+        for (year, month, day) in [
+            (2023, 2, 29),
+            (1900, 2, 29),
+            (2024, 4, 31),
+            (2024, 0, 1),
+            (2024, 13, 1),
+            (2024, 1, 0),
+        ] {
+            assert_eq!(
+                weekday_of(year, month, day),
+                None,
+                "Failed on {year}-{month}-{day}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_intervals_overlap() -> TestResult {
+        let at = |day| Datetime::from_ymd(2024, 1, day);
+        let (first, second, third, fourth) = (at(1)?, at(2)?, at(3)?, at(4)?);
+        // Touching
+        assert!(!intervals_overlap((first, second), (second, third)));
+        assert!(!intervals_overlap((second, third), (first, second)));
+        // Nested
+        assert!(intervals_overlap((first, fourth), (second, third)));
+        assert!(intervals_overlap((second, third), (first, fourth)));
+        assert!(intervals_overlap((first, third), (first, third)));
+        // Partial
+        assert!(intervals_overlap((first, third), (second, fourth)));
+        // Disjoint
+        assert!(!intervals_overlap((first, second), (third, fourth)));
+        // Empty or reversed intervals
+        assert!(!intervals_overlap((second, second), (first, third)));
+        assert!(!intervals_overlap((third, first), (first, fourth)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_rfc3339_nanos() -> TestResult {
+        let date = Datetime::from_rfc3339_nanos("2024-01-01T00:00:00.123456789Z")?;
+        assert_eq!(
+            (date.nanosecond, date.offset_minutes),
+            (123_456_789, Some(0))
+        );
+        let date = Datetime::from_rfc3339_nanos("2024-01-01T00:00:00.000000001-03:00")?;
+        assert_eq!((date.nanosecond, date.offset_minutes), (1, Some(-180)));
+        let date = Datetime::from_rfc3339_nanos("2024-01-01T00:00:00.999999999+00:00")?;
+        assert_eq!((date.second, date.nanosecond), (0, 999_999_999));
+        let date = Datetime::from_rfc3339_nanos("2024-01-01T00:00:00.12-00:00")?;
+        assert_eq!((date.nanosecond, date.offset_minutes), (120_000_000, None));
+        assert_eq!(
+            date.format("%Y-%m-%dT%H:%M:%S.%f")?,
+            "2024-01-01T00:00:00.120000000"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_from_ordinal() -> TestResult {
+        assert_eq!(
+            date_of(Datetime::checked_from_ordinal(2024, 1)?),
+            (2024, 1, 1)
+        );
+        assert_eq!(
+            date_of(Datetime::checked_from_ordinal(2024, 60)?),
+            (2024, 2, 29)
+        );
+        assert_eq!(
+            date_of(Datetime::checked_from_ordinal(2023, 60)?),
+            (2023, 3, 1)
+        );
+        assert_eq!(
+            date_of(Datetime::checked_from_ordinal(2024, 366)?),
+            (2024, 12, 31)
+        );
+        assert_eq!(
+            date_of(Datetime::checked_from_ordinal(2023, 365)?),
+            (2023, 12, 31)
+        );
+        for (year, ordinal) in [(2023, 366), (2024, 367), (2024, 0), (1900, 366)] {
+            assert!(
+                Datetime::checked_from_ordinal(year, ordinal).is_err(),
+                "Failed on {year}-{ordinal}"
+            );
+        }
+        let date = Datetime::from_ymd(2024, 8, 15)?;
+        assert_eq!(
+            Datetime::checked_from_ordinal(2024, date.day_of_year())?,
+            date
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_from_str() -> TestResult {
+        let date: Datetime = "2024-01-01".try_into()?;
+        assert_eq!(date, Datetime::from_ymd(2024, 1, 1)?);
+        let date = Datetime::try_from("15/10/2023")?;
+        assert_eq!(date, Datetime::from_ymd(2023, 10, 15)?);
+
+        let report = Datetime::try_from("next tuesday").expect_err("Not a date");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::Unrecognized { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_timestamp() -> TestResult {
+        let last = Datetime::from_unix_timestamp(i64::MAX)?;
+        assert_eq!((last.year, last.month, last.day), (292_277_026_596, 12, 4));
+        assert_eq!(last.checked_timestamp()?, i64::MAX);
+        assert_eq!(last.to_unix_timestamp(), i64::MAX);
+        let one_second_later = Datetime {
+            second: last.second + 1,
+            ..last
+        };
+        assert!(one_second_later.checked_timestamp().is_err());
+        assert!(Datetime { day: 5, ..last }.checked_timestamp().is_err());
+        for year in [292_277_026_597, 10_usize.pow(17), usize::MAX] {
+            let date = Datetime { year, ..last };
+            assert!(date.checked_timestamp().is_err(), "Failed on year {year}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_day_of_year() -> TestResult {
+        for (ordinal, padded) in [(5, "005"), (60, "060"), (300, "300")] {
+            let date = Datetime::checked_from_ordinal(2024, ordinal)?;
+            assert_eq!(date.format("%j")?, padded);
+            assert_eq!(date.format("%-j")?, ordinal.to_string());
+            let format = "%Y-%j";
+            assert_eq!(Datetime::from_str(&date.format(format)?, format)?, date);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_seconds_iter() -> TestResult {
+        let start = Datetime::try_from_ymd_hms(2024, 1, 1, 10, 0, 57)?;
+        let seconds: Vec<_> = start
+            .seconds_iter()
+            .take(5)
+            .map(|date| (date.minute, date.second))
+            .collect();
+        assert_eq!(seconds, vec![(0, 57), (0, 58), (0, 59), (1, 0), (1, 1)]);
+
+        let start = Datetime::try_from_ymd_hms(2024, 2, 29, 23, 59, 59)?;
+        let next = start.seconds_iter().nth(1).expect("Not the last second");
+        assert_eq!(next, Datetime::from_ymd(2024, 3, 1)?);
+
+        assert_eq!(Datetime::MAX, Datetime::from_unix_timestamp(i64::MAX)?);
+        let before_max = Datetime {
+            second: Datetime::MAX.second - 1,
+            ..Datetime::MAX
+        };
+        let last: Vec<_> = before_max.seconds_iter().collect();
+        assert_eq!(last, vec![before_max, Datetime::MAX]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zeroed() -> TestResult {
+        let zeroed = Datetime::zeroed();
+        assert_eq!((zeroed.year, zeroed.month, zeroed.day), (0, 1, 1));
+        assert_eq!(
+            (zeroed.hour, zeroed.minute, zeroed.second, zeroed.nanosecond),
+            (0, 0, 0, 0)
+        );
+        assert_eq!(zeroed.offset_minutes, None);
+        assert_eq!(zeroed, Datetime::from_ymd(0, 1, 1)?);
+        assert!(zeroed.add_days(-1).is_err());
+
+        let default = Datetime::default();
+        assert_eq!((default.year, default.month, default.day), (1900, 1, 1));
+        assert_eq!((default.hour, default.minute, default.second), (0, 0, 0));
+        assert!(zeroed < default);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_parse_relative_now() -> TestResult {
+        // Each pair is read around the same instant, a few seconds at most
+        let seconds_ahead = |input| -> Result<i64, Error> {
+            let date = Datetime::parse_relative_now(input)?;
+            Ok(date.as_utc_timestamp() - Datetime::now().as_utc_timestamp())
+        };
+        for (input, expected) in [
+            ("+1d", 86_400),
+            ("tomorrow", 86_400),
+            ("-2h", -7200),
+            ("+90s", 90),
+            ("today", 0),
+        ] {
+            let ahead = seconds_ahead(input)?;
+            assert!(
+                (expected - 5..=expected).contains(&ahead),
+                "Failed on {input}: {ahead}"
+            );
+        }
+        assert!(Datetime::parse_relative_now("soon").is_err());
+
+        let now = Datetime::now();
+        assert!(now > Datetime::from_ymd(2024, 1, 1)?);
+        assert_eq!(now.offset_minutes, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_relative() -> TestResult {
+        let reference = Datetime::try_from_ymd_hms(2024, 1, 1, 10, 30, 0)?;
+        let at = |year, month, day, hour, minute| {
+            Datetime::try_from_ymd_hms(year, month, day, hour, minute, 0)
+        };
+        let cases = [
+            ("today", at(2024, 1, 1, 10, 30)?),
+            ("yesterday", at(2023, 12, 31, 10, 30)?),
+            ("tomorrow", at(2024, 1, 2, 10, 30)?),
+            ("+1d", at(2024, 1, 2, 10, 30)?),
+            ("+3d", at(2024, 1, 4, 10, 30)?),
+            ("-2h", at(2024, 1, 1, 8, 30)?),
+            ("-11h", at(2023, 12, 31, 23, 30)?),
+            ("+1w", at(2024, 1, 8, 10, 30)?),
+            ("+90m", at(2024, 1, 1, 12, 0)?),
+            (
+                "+30s",
+                Datetime {
+                    second: 30,
+                    ..reference
+                },
+            ),
+            ("-0d", reference),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                Datetime::parse_relative(input, reference)?,
+                expected,
+                "Failed on {input}"
+            );
+        }
+        for input in [
+            "",
+            "now",
+            "Today",
+            "3d",
+            "+d",
+            "+3",
+            "+3y",
+            "+-3d",
+            "+3 d",
+            "+99999999999999999999d",
+            // Amounts that fit an `i64` but not the calendar
+            "+9223372036854700000d",
+            "-9223372036854700000d",
+            "+999999999999999w",
+            "+1317624576693539401w",
+            "+9223372036854775807s",
+        ] {
+            assert!(
+                Datetime::parse_relative(input, reference).is_err(),
+                "Failed on {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_guess_with_options() {
+        let guess = |century_pivot| {
+            let options = ParseOptions {
+                century_pivot,
+                ..ParseOptions::default()
+            };
+            Datetime::try_guess_with_options("99-12-31", &options).map(|date| date.year)
+        };
+        assert_eq!(
+            Datetime::try_guess("99-12-31").map(|date| date.year),
+            Some(1999)
+        );
+        assert_eq!(guess(50), Some(1999));
+        // A pivot past 99 places every two digit year in the 2000s
+        assert_eq!(guess(100), Some(2099));
+
+        // Day first layouts with the year at the end follow the pivot too
+        let guess = |century_pivot| {
+            let options = ParseOptions {
+                century_pivot,
+                ..ParseOptions::default()
+            };
+            Datetime::try_guess_with_options("31/12/99", &options).map(|date| date.year)
+        };
+        assert_eq!(guess(25), Some(1999));
+        assert_eq!(guess(100), Some(2099));
+    }
+
+    #[test]
+    fn test_monotonic_key() -> TestResult {
+        let mut dates = vec![
+            Datetime::zeroed(),
+            Datetime::epoch(),
+            Datetime::try_from_ymd_hms(1999, 12, 31, 23, 59, 59)?,
+            Datetime::try_from_ymd_hms(2000, 1, 1, 0, 0, 0)?,
+            Datetime::try_from_ymd_hms(2024, 1, 31, 0, 0, 0)?,
+            Datetime::try_from_ymd_hms(2024, 2, 1, 0, 0, 0)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 12, 30, 0)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 12, 30, 1)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 13, 0, 0)?,
+            Datetime::MAX,
+        ];
+        for date in &dates {
+            assert_eq!(Datetime::from_monotonic_key(date.monotonic_key())?, *date);
+        }
+        dates.sort();
+        let keys: Vec<u64> = dates.iter().map(Datetime::monotonic_key).collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        // Day 31 of February
+        let february = Datetime::try_from_ymd_hms(2024, 2, 1, 0, 0, 0)?;
+        assert!(Datetime::from_monotonic_key(february.monotonic_key() + 30 * 86400).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_as_range_end() -> TestResult {
+        let start = Datetime::from_str("2024-01-01", "%Y-%m-%d")?;
+        let end = Datetime::parse_as_range_end("2024-01-01", "%Y-%m-%d")?;
+        assert_eq!(start, Datetime::try_from_ymd_hms(2024, 1, 1, 0, 0, 0)?);
+        assert_eq!(end, Datetime::try_from_ymd_hms(2024, 1, 1, 23, 59, 59)?);
+        // Only the fields missing from the input are moved
+        let end = Datetime::parse_as_range_end("2024-01-01 10", "%Y-%m-%d %H")?;
+        assert_eq!(end, Datetime::try_from_ymd_hms(2024, 1, 1, 10, 59, 59)?);
+        let end = Datetime::parse_as_range_end("2024-01-01 10:00:00", "%Y-%m-%d %H:%M:%S")?;
+        assert_eq!(end, Datetime::try_from_ymd_hms(2024, 1, 1, 10, 0, 0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weekday_and_month_names() -> TestResult {
+        let monday = Datetime::from_ymd(2024, 1, 1)?;
+        assert_eq!(monday.weekday().to_string(), "Monday");
+        assert_eq!(monday.month_of_year().to_string(), "January");
+        for weekday in Weekday::ALL {
+            assert_eq!(weekday.to_string().parse::<Weekday>()?, weekday);
+        }
+        for month in Month::ALL {
+            assert_eq!(month.to_string().parse::<Month>()?, month);
+        }
+        assert_eq!("sun".parse::<Weekday>()?, Weekday::Sunday);
+        assert_eq!("SEP".parse::<Month>()?, Month::September);
+        for name in ["", "Mon day", "Mondays", "Funday"] {
+            let report = name.parse::<Weekday>().expect_err("Not a weekday");
+            assert!(matches!(
+                report.downcast_ref::<DatetimeError>(),
+                Some(DatetimeError::UnknownName {
+                    kind: "weekday",
+                    ..
+                })
+            ));
+        }
+        assert!("Smarch".parse::<Month>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_excel_serial() -> TestResult {
+        let cases = [
+            (Datetime::from_ymd(2024, 1, 1)?, 45292.0),
+            (Datetime::from_ymd(1970, 1, 1)?, 25569.0),
+            (Datetime::from_ymd(1900, 3, 1)?, 61.0),
+            (Datetime::from_ymd(1900, 2, 28)?, 59.0),
+            (Datetime::from_ymd(1900, 1, 1)?, 1.0),
+            (Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 0)?, 45292.5),
+            (Datetime::try_from_ymd_hms(2024, 1, 1, 18, 0, 0)?, 45292.75),
+        ];
+        for (date, serial) in cases {
+            assert_eq!(date.to_excel_serial(), serial, "Failed on {date}");
+            assert_eq!(
+                Datetime::from_excel_serial(serial)?,
+                date,
+                "Failed on {serial}"
+            );
+        }
+        let date = Datetime::from_excel_serial(45292.0 + 1.0 / 3.0)?;
+        assert_eq!((date.hour, date.minute, date.second), (8, 0, 0));
+        for serial in [60.0, 60.5, f64::NAN, f64::INFINITY, -1e20] {
+            assert!(
+                Datetime::from_excel_serial(serial).is_err(),
+                "Failed on {serial}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_week_start() -> TestResult {
+        let wednesday = Datetime::try_from_ymd_hms(2024, 1, 3, 15, 30, 0)?;
+        assert_eq!(wednesday.week_start(true), Datetime::from_ymd(2024, 1, 1)?);
+        assert_eq!(
+            wednesday.week_start(false),
+            Datetime::from_ymd(2023, 12, 31)?
+        );
+        // Dates already on the week start only lose their time
+        let monday = Datetime::try_from_ymd_hms(2024, 1, 1, 8, 0, 0)?;
+        assert_eq!(monday.week_start(true), Datetime::from_ymd(2024, 1, 1)?);
+        assert_eq!(monday.week_start(false), Datetime::from_ymd(2023, 12, 31)?);
+        let sunday = Datetime::from_ymd(2024, 1, 7)?;
+        assert_eq!(sunday.week_start(false), sunday);
+        assert_eq!(sunday.week_start(true), Datetime::from_ymd(2024, 1, 1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interpolate() -> TestResult {
+        let midnight = Datetime::from_ymd(2024, 1, 1)?;
+        let next_midnight = Datetime::from_ymd(2024, 1, 2)?;
+        let noon = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 0)?;
+        let cases = [
+            (0.0, midnight),
+            (0.5, noon),
+            (0.25, Datetime::try_from_ymd_hms(2024, 1, 1, 6, 0, 0)?),
+            (1.0, next_midnight),
+            (-1.0, midnight),
+            (2.0, next_midnight),
+        ];
+        for (t, expected) in cases {
+            assert_eq!(
+                Datetime::interpolate(midnight, next_midnight, t),
+                expected,
+                "Failed on {t}"
+            );
+        }
+        // Going backwards in time
+        assert_eq!(Datetime::interpolate(next_midnight, midnight, 0.5), noon);
+
+        // Fractions of a second and the offset of `a` are kept
+        let a = Datetime::parse_rfc3339("2024-01-01T10:00:00.25+02:00")?;
+        let b = Datetime::parse_rfc3339("2024-01-01T10:00:01.75Z")?;
+        assert_eq!(Datetime::interpolate(a, b, 0.0), a);
+        let middle = Datetime::interpolate(a, b, 0.5);
+        assert_eq!((middle.second, middle.nanosecond), (1, 0));
+        assert_eq!(middle.offset_minutes, Some(120));
+        let end = Datetime::interpolate(a, b, 1.0);
+        assert_eq!((end.second, end.nanosecond), (1, 750_000_000));
+        assert_eq!(end.offset_minutes, Some(120));
+        let abbreviated = Datetime {
+            zone_abbreviation: ZoneAbbreviation::find("EET"),
+            ..a
+        };
+        let middle = Datetime::interpolate(abbreviated, b, 0.5);
+        assert_eq!(middle.zone_abbreviation, abbreviated.zone_abbreviation);
+
+        // The whole supported range
+        let (first, last) = (Datetime::zeroed(), Datetime::MAX);
+        assert_eq!(Datetime::interpolate(first, last, 0.0), first);
+        assert_eq!(Datetime::interpolate(first, last, 1.0), last);
+        let middle = Datetime::interpolate(first, last, 0.5);
+        assert!(first < middle && middle < last);
+        assert_eq!(Datetime::interpolate(last, first, 1.0), first);
+        Ok(())
+    }
+
+    #[test]
+    fn test_week_of_year() -> TestResult {
+        // 2024-01-01 is a Monday and 2023-01-01 a Sunday
+        let cases = [
+            ((2024, 1, 1), 1, 0),
+            ((2024, 1, 6), 1, 0),
+            ((2024, 1, 7), 1, 1),
+            ((2024, 1, 8), 2, 1),
+            ((2024, 12, 31), 53, 52),
+            ((2023, 1, 1), 0, 1),
+            ((2023, 1, 2), 1, 1),
+        ];
+        for ((year, month, day), monday_week, sunday_week) in cases {
+            let date = Datetime::from_ymd(year, month, day)?;
+            assert_eq!(date.week_of_year(true), monday_week, "Failed on {date}");
+            assert_eq!(date.week_of_year(false), sunday_week, "Failed on {date}");
+            for monday_first in [true, false] {
+                let week = date.week_of_year(monday_first);
+                let back = Datetime::from_year_week(year, week, date.weekday(), monday_first)?;
+                assert_eq!(back, date, "Failed on {date}");
+            }
+        }
+        // Week 0 of 2024 has no Sunday in 2024
+        assert!(Datetime::from_year_week(2024, 0, Weekday::Sunday, true).is_err());
+        assert!(Datetime::from_year_week(2024, 54, Weekday::Monday, true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_week_numbers() -> TestResult {
+        let cases = [
+            ((2021, 1, 1), "2020-W53 00 00"),
+            ((2024, 1, 1), "2024-W01 01 00"),
+            ((2024, 1, 7), "2024-W01 01 01"),
+            ((2024, 3, 4), "2024-W10 10 09"),
+            ((2024, 12, 30), "2025-W01 53 52"),
+        ];
+        for ((year, month, day), expected) in cases {
+            let date = Datetime::from_ymd(year, month, day)?;
+            assert_eq!(date.format("%G-W%V %W %U")?, expected, "Failed on {date}");
+        }
+        assert_eq!(Datetime::from_ymd(2024, 1, 1)?.format("%-V")?, "1");
+        assert_eq!(Datetime::from_ymd(2024, 1, 7)?.format("%u %w")?, "7 0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_set_day() -> TestResult {
+        let leap = Datetime::try_from_ymd_hms(2024, 2, 1, 10, 0, 0)?;
+        assert_eq!(
+            leap.checked_set_day(29)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 10, 0, 0)?
+        );
+        let common = Datetime::from_ymd(2023, 2, 1)?;
+        assert_eq!(
+            common.checked_set_day(28)?,
+            Datetime::from_ymd(2023, 2, 28)?
+        );
+        for (date, day) in [(leap, 30), (common, 29), (common, 0)] {
+            let report = date.checked_set_day(day).expect_err("Not in February");
+            assert!(matches!(
+                report.downcast_ref::<DatetimeError>(),
+                Some(DatetimeError::InvalidValue {
+                    field: Token::Day,
+                    ..
+                })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_duration_since() -> TestResult {
+        let now = Datetime::try_from_ymd_hms(2024, 1, 10, 12, 0, 0)?;
+        let cases = [
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 12, 0, 0)?,
+                "just now",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 11, 59, 59)?,
+                "1 second ago",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 11, 59, 15)?,
+                "45 seconds ago",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 12, 0, 30)?,
+                "in 30 seconds",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 11, 55, 0)?,
+                "5 minutes ago",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 12, 1, 59)?,
+                "in 1 minute",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 9, 30, 0)?,
+                "2 hours ago",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 10, 14, 0, 0)?,
+                "in 2 hours",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 1, 7, 12, 0, 0)?,
+                "3 days ago",
+            ),
+            (
+                Datetime::try_from_ymd_hms(2024, 2, 10, 13, 0, 0)?,
+                "in 31 days",
+            ),
+        ];
+        for (earlier, expected) in cases {
+            assert_eq!(
+                now.format_duration_since(&earlier),
+                expected,
+                "Failed on {earlier}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmp_ignoring() -> TestResult {
+        use std::cmp::Ordering;
+        let a = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 30)?;
+        let b = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 45)?;
+        let seconds = FieldMask::SECOND | FieldMask::NANOSECOND;
+        assert_eq!(a.cmp_ignoring(&b, FieldMask::NONE), Ordering::Less);
+        assert_eq!(a.cmp_ignoring(&b, seconds), Ordering::Equal);
+
+        let c = Datetime::try_from_ymd_hms(2024, 1, 1, 13, 59, 0)?;
+        assert_eq!(c.cmp_ignoring(&b, seconds), Ordering::Greater);
+        let minutes = seconds | FieldMask::MINUTE;
+        let hours = minutes | FieldMask::HOUR;
+        assert_eq!(c.cmp_ignoring(&b, minutes), Ordering::Greater);
+        assert_eq!(c.cmp_ignoring(&b, hours), Ordering::Equal);
+        // Leading fields may be left out too, like the year of birthdays
+        let next_year = Datetime::try_from_ymd_hms(2025, 1, 1, 12, 0, 30)?;
+        assert_eq!(next_year.cmp_ignoring(&a, FieldMask::YEAR), Ordering::Equal);
+
+        let offset = Datetime {
+            offset_minutes: Some(60),
+            ..a
+        };
+        assert_ne!(offset.cmp_ignoring(&a, FieldMask::NONE), Ordering::Equal);
+        assert_eq!(offset.cmp_ignoring(&a, FieldMask::OFFSET), Ordering::Equal);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_lenient() -> TestResult {
+        let cases = [
+            ("1/2/24", "%d/%m/%y", (2024, 2, 1, 0, 0)),
+            ("  2024-3-7  ", "%Y-%m-%d", (2024, 3, 7, 0, 0)),
+            ("7 MARCH 2024", "%d %B %Y", (2024, 3, 7, 0, 0)),
+            (
+                "2024-02-30 9:5 pm",
+                "%Y-%m-%d %I:%M %p",
+                (2024, 2, 29, 21, 5),
+            ),
+            (
+                "2024//03//07   10:30",
+                "%Y/%m/%d %H:%M",
+                (2024, 3, 7, 10, 30),
+            ),
+            ("Mar-2024", "%m-%Y", (2024, 3, 1, 0, 0)),
+        ];
+        for (input, format, expected) in cases {
+            let date = Datetime::parse_lenient(input, format)?;
+            assert_eq!(
+                (date.year, date.month, date.day, date.hour, date.minute),
+                expected,
+                "Failed on {input}"
+            );
+        }
+        assert!(Datetime::parse_lenient("2024-3-7", "%d/%m/%Y").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes() -> TestResult {
+        let dates = [
+            Datetime::zeroed(),
+            Datetime::try_from_ymd_hms(2024, 2, 29, 13, 45, 10)?,
+            Datetime {
+                nanosecond: 123_456_789,
+                offset_minutes: Some(-330),
+                ..Datetime::try_from_ymd_hms(1999, 12, 31, 23, 59, 59)?
+            },
+            Datetime {
+                offset_minutes: Some(0),
+                ..Datetime::MAX
+            },
+        ];
+        for date in dates {
+            assert_eq!(Datetime::from_bytes(&date.to_bytes())?, date);
+        }
+        let mut bytes = dates[1].to_bytes();
+        bytes[8] = 13;
+        let report = Datetime::from_bytes(&bytes).expect_err("Month 13");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidValue {
+                field: Token::FullMonth,
+                ..
+            })
+        ));
+        let mut bytes = dates[1].to_bytes();
+        bytes[17] = 2;
+        assert!(Datetime::from_bytes(&bytes).is_err());
+        let report = Datetime::from_bytes(&bytes[..19]).expect_err("Too short");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidEncoding { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_age_in_units() -> TestResult {
+        let birth = Datetime::try_from_ymd_hms(2000, 5, 17, 8, 0, 0)?;
+        let now = Datetime::try_from_ymd_hms(2024, 5, 17, 7, 59, 59)?;
+        // One second short of the 24th birthday
+        assert_eq!(birth.age_in_units(&now, TimeUnit::Year), 23);
+        assert_eq!(birth.age_in_units(&now, TimeUnit::Month), 287);
+        assert_eq!(birth.age_in_units(&now, TimeUnit::Day), 8765);
+        assert_eq!(birth.age_in_units(&now, TimeUnit::Hour), 8765 * 24 + 23);
+        let birthday = Datetime::try_from_ymd_hms(2024, 5, 17, 8, 0, 0)?;
+        assert_eq!(birth.age_in_units(&birthday, TimeUnit::Year), 24);
+        assert_eq!(birth.age_in_units(&birthday, TimeUnit::Month), 288);
+
+        // Backwards in time the counts are negative
+        assert_eq!(now.age_in_units(&birth, TimeUnit::Year), -23);
+        assert_eq!(now.age_in_units(&birth, TimeUnit::Month), -287);
+        assert_eq!(now.age_in_units(&birth, TimeUnit::Second), -757_382_399);
+
+        let january = Datetime::from_ymd(2024, 1, 31)?;
+        assert_eq!(
+            january.age_in_units(&Datetime::from_ymd(2024, 2, 29)?, TimeUnit::Month),
+            1
+        );
+        assert_eq!(
+            january.age_in_units(&Datetime::from_ymd(2024, 2, 28)?, TimeUnit::Month),
+            0
+        );
+        assert_eq!(january.age_in_units(&january, TimeUnit::Day), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seconds_in_day() -> TestResult {
+        let date = Datetime::try_from_ymd_hms(2024, 1, 1, 1, 0, 30)?;
+        assert_eq!(date.total_seconds_in_day(), 3630);
+        assert_eq!(Datetime::from_ymd(2024, 1, 1)?.total_seconds_in_day(), 0);
+        for seconds in [0, 3630, 43_200, 86_399] {
+            let time = Datetime::from_seconds_in_day(seconds)?;
+            assert_eq!(time.total_seconds_in_day(), seconds);
+        }
+        let last = Datetime::from_seconds_in_day(86_399)?;
+        assert_eq!((last.hour, last.minute, last.second), (23, 59, 59));
+        assert!(Datetime::from_seconds_in_day(86_400).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_round_trip() -> TestResult {
+        let cases = [
+            ("2024", "%Y"),
+            ("2024 24", "%Y %y"),
+            ("2024-03", "%Y-%m"),
+            ("2024 March", "%Y %B"),
+            ("2024 Mar 07", "%Y %b %d"),
+            ("2024 7", "%Y %-d"),
+            ("2024- 7", "%Y-%e"),
+            ("2024 067", "%Y %j"),
+            ("Thursday 2024-03-07", "%A %Y-%m-%d"),
+            ("Thu 2024-03-07", "%a %Y-%m-%d"),
+            ("4 2024-03-07", "%u %Y-%m-%d"),
+            ("4 2024-03-07", "%w %Y-%m-%d"),
+            ("2024-W10-4", "%G-W%V-%u"),
+            ("2024 10 4", "%Y %W %u"),
+            ("2024 09 4", "%Y %U %u"),
+            ("13:45:10", "%H:%M:%S"),
+            ("01:45 PM", "%I:%M %p"),
+            ("12:00 AM", "%I:%M %p"),
+            ("13:45:10.123456789", "%H:%M:%S.%f"),
+            ("13:45+0530", "%H:%M%z"),
+            ("13:45-0000", "%H:%M%z"),
+            ("13:45:10", "%T"),
+            ("13:45", "%R"),
+            ("1700000000", "%s"),
+            ("Thu Mar  7 13:45:10 2024", "%c"),
+            ("100% 2024", "100%% %Y"),
+        ];
+        for (input, format) in cases {
+            let date = Datetime::from_str(input, format)?;
+            assert_eq!(date.format(format)?, input, "Failed on {format}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_or() -> TestResult {
+        let date = Datetime::parse_or("2024-05-17", "%Y-%m-%d", "%d/%m/%Y")?;
+        assert_eq!((date.year, date.month, date.day), (2024, 5, 17));
+        let date = Datetime::parse_or("17/05/2024", "%Y-%m-%d", "%d/%m/%Y")?;
+        assert_eq!((date.year, date.month, date.day), (2024, 5, 17));
+
+        let report =
+            Datetime::parse_or("2024-13-17", "%Y-%m-%d", "%d/%m/%Y").expect_err("Neither matches");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidValue {
+                field: Token::FullMonth,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_fields() -> TestResult {
+        let date = Datetime {
+            offset_minutes: Some(60),
+            ..Datetime::try_from_ymd_hms(2024, 1, 1, 10, 20, 20)?
+        };
+        assert_eq!(date.map_fields(|_, value| value)?, date);
+        let double_seconds = |field, value| {
+            if field == Field::Second {
+                value * 2
+            } else {
+                value
+            }
+        };
+        let doubled = date.map_fields(double_seconds)?;
+        assert_eq!((doubled.second, doubled.offset_minutes), (40, Some(60)));
+        // 40 seconds doubled is past the end of the minute
+        let report = doubled.map_fields(double_seconds).expect_err("80 seconds");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::InvalidValue {
+                field: Token::Second,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_gps_seconds() -> TestResult {
+        let epoch = Datetime::from_ymd(1980, 1, 6)?;
+        assert_eq!(epoch.to_gps_seconds(), 0);
+        assert_eq!(Datetime::from_gps_seconds(0)?, epoch);
+        let date = Datetime::from_ymd(2024, 1, 1)?;
+        assert_eq!(date.to_gps_seconds(), 1_388_102_400);
+        assert_eq!(Datetime::from_gps_seconds(1_388_102_400)?, date);
+        assert_eq!(Datetime::epoch().to_gps_seconds(), -315_964_800);
+        assert!(Datetime::from_gps_seconds(i64::MAX).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_utc_timestamp() -> TestResult {
+        let local = Datetime::parse_rfc3339("2024-05-17T15:45:10+02:00")?;
+        let utc = Datetime::parse_rfc3339("2024-05-17T13:45:10Z")?;
+        assert_eq!(local.as_utc_timestamp(), utc.as_utc_timestamp());
+        assert_eq!(utc.as_utc_timestamp(), utc.to_unix_timestamp());
+        assert_eq!(local.to_unix_timestamp() - local.as_utc_timestamp(), 7200);
+
+        let west = Datetime::parse_rfc3339("2024-05-17T08:15:10-05:30")?;
+        assert_eq!(west.as_utc_timestamp(), utc.as_utc_timestamp());
+        // Without an offset, or with an unknown one, the fields are UTC
+        let naive = Datetime::from_str("2024-05-17 13:45:10", "%Y-%m-%d %T")?;
+        assert_eq!(naive.as_utc_timestamp(), utc.as_utc_timestamp());
+        let unknown = Datetime::parse_rfc3339("2024-05-17T13:45:10-00:00")?;
+        assert_eq!(unknown.as_utc_timestamp(), utc.as_utc_timestamp());
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_instant() -> TestResult {
+        let noon = Datetime::try_from_ymd_hms(2024, 1, 1, 12, 0, 0)?.with_offset(120)?;
+        let utc = Datetime::try_from_ymd_hms(2024, 1, 1, 10, 0, 0)?.with_offset(0)?;
+        assert!(noon.same_instant(&utc));
+        assert_ne!(noon, utc);
+        // Same wall clock, different moments
+        let other_zone = noon.with_offset(-180)?;
+        assert_eq!(
+            (other_zone.hour, other_zone.offset_minutes),
+            (12, Some(-180))
+        );
+        assert!(!noon.same_instant(&other_zone));
+        assert_ne!(noon, other_zone);
+        // Across midnight, and without an offset meaning UTC
+        let early = Datetime::try_from_ymd_hms(2024, 1, 2, 1, 30, 0)?.with_offset(330)?;
+        let late = Datetime::try_from_ymd_hms(2024, 1, 1, 20, 0, 0)?;
+        assert!(early.same_instant(&late));
+        assert!(noon.same_instant(&noon));
+
+        assert!(noon.with_offset(24 * 60).is_err());
+        assert!(noon.with_offset(i32::MIN).is_err());
+        assert!(noon.with_offset(-(24 * 60 - 1)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_range() -> TestResult {
+        let (start, end) = Datetime::parse_range("2024-01-01 to 2024-12-31", "%Y-%m-%d", " to ")?;
+        assert_eq!(start, Datetime::from_ymd(2024, 1, 1)?);
+        assert_eq!(end, Datetime::from_ymd(2024, 12, 31)?);
+
+        let report = Datetime::parse_range("2024-01-01 - 2024-12-31", "%Y-%m-%d", " to ")
+            .expect_err("No separator");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::MissingSeparator { .. })
+        ));
+        for input in [
+            "2024-01-01 to 2024-13-31",
+            "2024-01-01 to ",
+            "x to 2024-12-31",
+        ] {
+            assert!(
+                Datetime::parse_range(input, "%Y-%m-%d", " to ").is_err(),
+                "Failed on {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_exact_len() -> TestResult {
+        assert_eq!(
+            Datetime::parse_exact_len("2024-02-29 12:00", "%Y-%m-%d %H:%M", 16)?,
+            Datetime::try_from_ymd_hms(2024, 2, 29, 12, 0, 0)?
+        );
+        for (input, format) in [
+            ("2024-02-2", "%Y-%m-%d"),
+            ("2024-02-29 ", "%Y-%m-%d"),
+            // `%d` alone would accept a single digit day
+            ("2024-02-1", "%Y-%m-%d"),
+            ("", "%Y-%m-%d"),
+        ] {
+            let report = Datetime::parse_exact_len(input, format, 10).expect_err("Wrong length");
+            assert!(
+                matches!(
+                    report.downcast_ref::<DatetimeError>(),
+                    Some(DatetimeError::UnexpectedLength { expected: 10, .. })
+                ),
+                "Failed on {input:?}"
+            );
+        }
+        // The length is checked first, then the format as usual
+        assert!(Datetime::parse_exact_len("2024-02-30", "%Y-%m-%d", 10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_fixed_columns() -> TestResult {
+        let spec = [
+            (Field::Year, 0, 4),
+            (Field::Month, 4, 2),
+            (Field::Day, 6, 2),
+        ];
+        assert_eq!(
+            Datetime::parse_fixed_columns("20240229", &spec)?,
+            Datetime::from_ymd(2024, 2, 29)?
+        );
+        let record = "ACC00117  20231231 2359";
+        let spec = [
+            (Field::Year, 10, 4),
+            (Field::Month, 14, 2),
+            (Field::Day, 16, 2),
+            (Field::Hour, 19, 2),
+            (Field::Minute, 21, 2),
+        ];
+        let date = Datetime::parse_fixed_columns(record, &spec)?;
+        assert_eq!(
+            (date.year, date.day, date.hour, date.minute),
+            (2023, 31, 23, 59)
+        );
+        assert_eq!(
+            Datetime::parse_fixed_columns("2024", &[(Field::Year, 0, 4)])?,
+            Datetime::from_ymd(2024, 1, 1)?
+        );
+
+        let report = Datetime::parse_fixed_columns("20240229", &[(Field::Day, 6, 4)])
+            .expect_err("Past the end");
+        assert!(matches!(
+            report.downcast_ref::<DatetimeError>(),
+            Some(DatetimeError::ColumnOutOfBounds {
+                start: 6,
+                end: 10,
+                ..
+            })
+        ));
+        assert!(Datetime::parse_fixed_columns("2024", &[(Field::Year, usize::MAX, 2)]).is_err());
+        for input in ["2024-229", "20230229", "2024+229"] {
+            assert!(
+                Datetime::parse_fixed_columns(
+                    input,
+                    &[
+                        (Field::Year, 0, 4),
+                        (Field::Month, 4, 2),
+                        (Field::Day, 6, 2)
+                    ]
+                )
+                .is_err(),
+                "Failed on {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_snap_to_grid() -> TestResult {
+        let midnight = Datetime::from_ymd(2024, 3, 1)?;
+        let quarter = Duration::minutes(15);
+        let at = |time: &str| Datetime::from_str(&format!("2024-03-01 {time}"), "%Y-%m-%d %T");
+        let cases = [
+            ("12:07:00", "12:00:00"),
+            ("12:07:29", "12:00:00"),
+            ("12:07:30", "12:15:00"),
+            ("12:08:00", "12:15:00"),
+            ("12:15:00", "12:15:00"),
+            ("00:00:00", "00:00:00"),
+            ("23:52:00", "23:45:00"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                at(input)?.snap_to_grid(midnight, quarter),
+                at(expected)?,
+                "Failed on {input}"
+            );
+        }
+        assert_eq!(
+            at("23:55:00")?.snap_to_grid(midnight, quarter),
+            Datetime::from_ymd(2024, 3, 2)?
+        );
+        // Points before the origin, and a negative step, snap the same way
+        assert_eq!(
+            Datetime::from_str("2024-02-29 23:52", "%Y-%m-%d %H:%M")?
+                .snap_to_grid(midnight, -quarter),
+            Datetime::from_str("2024-02-29 23:45", "%Y-%m-%d %H:%M")?
+        );
+        let mut fraction = at("12:07:30")?;
+        fraction.nanosecond = 1;
+        assert_eq!(fraction.snap_to_grid(midnight, quarter), at("12:15:00")?);
+        fraction.second = 29;
+        fraction.nanosecond = 999_999_999;
+        assert_eq!(fraction.snap_to_grid(midnight, quarter), at("12:00:00")?);
+        assert_eq!(
+            at("12:07:00")?.snap_to_grid(midnight, Duration::seconds(0)),
+            midnight
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_leap_seconds_between() -> TestResult {
+        let table = LeapSecondTable::new([Datetime::from_ymd(2016, 12, 31)?])?;
+        let at = |date: &str| Datetime::from_str(date, "%Y-%m-%d %T");
+        let before = at("2016-12-31 23:59:59")?;
+        let after = at("2017-01-01 00:00:00")?;
+        assert_eq!(before.leap_seconds_between(&after, &table), 1);
+        assert_eq!(after.leap_seconds_between(&before, &table), -1);
+        assert_eq!(
+            before.to_unix_timestamp() - after.to_unix_timestamp()
+                + after.leap_seconds_between(&before, &table),
+            -2
+        );
+        // Intervals that end before the insertion or start after it
+        let earlier = at("2016-06-01 00:00:00")?;
+        assert_eq!(earlier.leap_seconds_between(&before, &table), 0);
+        assert_eq!(
+            after.leap_seconds_between(&at("2018-01-01 00:00:00")?, &table),
+            0
+        );
+        assert_eq!(earlier.leap_seconds_between(&earlier, &table), 0);
+        // The offset moves the local midnight away from the UTC one
+        let local = at("2017-01-01 00:30:00")?.with_offset(60)?;
+        assert_eq!(before.leap_seconds_between(&local, &table), 0);
+        assert_eq!(
+            earlier.leap_seconds_between(&after, &LeapSecondTable::default()),
+            0
+        );
+
+        let table = LeapSecondTable::new([
+            Datetime::from_ymd(2016, 12, 31)?,
+            at("2015-06-30 12:00:00")?,
+            Datetime::from_ymd(2016, 12, 31)?,
+        ])?;
+        assert_eq!(table.len(), 2);
+        let start = Datetime::from_ymd(2015, 1, 1)?;
+        assert_eq!(start.leap_seconds_between(&after, &table), 2);
+
+        // Days that are not dates, or end too late, are errors
+        assert!(
+            LeapSecondTable::new([Datetime {
+                day: 31,
+                ..start.add_months(1)?
+            }])
+            .is_err()
+        );
+        assert!(LeapSecondTable::new([Datetime::MAX]).is_err());
+        let before_max = Datetime::MAX.add_days(-1)?;
+        assert_eq!(LeapSecondTable::new([before_max])?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_from_parts() -> TestResult {
+        assert_eq!(
+            Datetime::checked_from_parts(&[2024, 2, 29, 23, 59, 59]),
+            Ok(Datetime::try_from_ymd_hms(2024, 2, 29, 23, 59, 59)?)
+        );
+        let cases = [
+            ([2024, 13, 40, 0, 0, 0], FieldMask::MONTH | FieldMask::DAY),
+            ([2023, 2, 29, 0, 0, 0], FieldMask::DAY),
+            ([2024, 0, 31, 24, 0, 0], FieldMask::MONTH | FieldMask::HOUR),
+            (
+                [2024, 1, 0, 12, 60, 60],
+                FieldMask::DAY | FieldMask::MINUTE | FieldMask::SECOND,
+            ),
+            (
+                [0, 99, 99, 99, 99, 99],
+                FieldMask::MONTH
+                    | FieldMask::DAY
+                    | FieldMask::HOUR
+                    | FieldMask::MINUTE
+                    | FieldMask::SECOND,
+            ),
+        ];
+        for (parts, expected) in cases {
+            assert_eq!(
+                Datetime::checked_from_parts(&parts),
+                Err(expected),
+                "Failed on parts: {parts:?}"
+            );
+        }
+        assert_eq!(
+            Datetime::checked_from_parts(&[2024, 13, 40, 0, 0, 0]).map_err(FieldMask::bits),
+            Err(0b0000_0110)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_weekday() -> TestResult {
+        let start = Datetime::from_ymd(2024, 1, 1)?;
+        let end = Datetime::from_ymd(2024, 3, 31)?;
+        // 4 in January, 4 in February and 5 in March
+        assert_eq!(start.count_weekday(&end, Weekday::Friday), 13);
+        assert_eq!(start.count_weekday(&end, Weekday::Monday), 13);
+        assert_eq!(start.count_weekday(&end, Weekday::Sunday), 13);
+        assert_eq!(start.count_weekday(&end, Weekday::Tuesday), 13);
+        assert_eq!(start.count_weekday(&end, Weekday::Saturday), 13);
+
+        // Both ends on the target, from Friday the 5th to Friday the 26th
+        let first = Datetime::try_from_ymd_hms(2024, 1, 5, 18, 0, 0)?;
+        let last = Datetime::from_ymd(2024, 1, 26)?;
+        assert_eq!(first.count_weekday(&last, Weekday::Friday), 4);
+        assert_eq!(first.count_weekday(&first, Weekday::Friday), 1);
+        assert_eq!(first.count_weekday(&first, Weekday::Thursday), 0);
+        // Ranges shorter than a week, and backwards ones
+        let saturday = Datetime::from_ymd(2024, 1, 6)?;
+        assert_eq!(first.count_weekday(&saturday, Weekday::Sunday), 0);
+        assert_eq!(last.count_weekday(&first, Weekday::Friday), 0);
+        Ok(())
     }
 }
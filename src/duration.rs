@@ -0,0 +1,259 @@
+use std::ops::{Add, Neg, Sub};
+
+use miette::{Diagnostic, Error};
+use thiserror::Error;
+
+/// A signed span of time with a resolution of one second
+#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord, Default)]
+pub struct Duration {
+    seconds: i64,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+pub(crate) enum DurationError {
+    #[error("Invalid ISO 8601 duration `{}`: {}", src, reason)]
+    Malformed { src: String, reason: String },
+    #[error(
+        "The `{}` component of `{}` has no fixed length in seconds",
+        designator,
+        src
+    )]
+    #[diagnostic(help("Express the duration in weeks, days or smaller units"))]
+    CalendarComponent { src: String, designator: char },
+}
+
+/// Designators allowed before the `T`, in order, with their length in
+/// seconds. Years and months vary in length, so they have none.
+const DATE_DESIGNATORS: &[(char, Option<i64>)] = &[
+    ('Y', None),
+    ('M', None),
+    ('W', Some(604_800)),
+    ('D', Some(86_400)),
+];
+/// Designators allowed after the `T`, in order
+const TIME_DESIGNATORS: &[(char, Option<i64>)] =
+    &[('H', Some(3600)), ('M', Some(60)), ('S', Some(1))];
+
+impl Duration {
+    pub const fn seconds(seconds: i64) -> Self {
+        Self { seconds }
+    }
+    /// A duration of `minutes` minutes. Like [`Duration::hours`] and
+    /// [`Duration::days`], panics if it does not fit in 64 bits of seconds.
+    pub const fn minutes(minutes: i64) -> Self {
+        Self::scaled(minutes, 60)
+    }
+    pub const fn hours(hours: i64) -> Self {
+        Self::scaled(hours, 3600)
+    }
+    pub const fn days(days: i64) -> Self {
+        Self::scaled(days, 86_400)
+    }
+    const fn scaled(value: i64, seconds: i64) -> Self {
+        match value.checked_mul(seconds) {
+            Some(seconds) => Self { seconds },
+            None => panic!("Duration overflowed 64 bits of seconds"),
+        }
+    }
+    /// Total length of the duration in seconds
+    pub fn as_seconds(&self) -> i64 {
+        self.seconds
+    }
+    /// The same length of time, but never negative
+    pub fn abs(self) -> Self {
+        Self {
+            seconds: self
+                .seconds
+                .checked_abs()
+                .expect("Duration overflowed 64 bits of seconds"),
+        }
+    }
+    /// Parses an ISO 8601 duration in the `PnYnMnWnDTnHnMnS` form, e.g.:
+    /// `P1DT2H30M`. Every component is an integer and they must appear in
+    /// that order, each at most once.
+    ///
+    /// Years and months are rejected instead of approximated: a month may
+    /// have 28 to 31 days, so `P1M` is not a fixed number of seconds. Weeks
+    /// and days are taken as exactly 7 and 1 times 86400 seconds.
+    /// # Examples
+    /// ```
+    /// use datetime::duration::Duration;
+    /// let duration = Duration::parse_iso8601("PT90M").unwrap();
+    /// assert_eq!(duration.as_seconds(), 5400);
+    /// ```
+    pub fn parse_iso8601(input: &str) -> Result<Self, Error> {
+        let malformed = |reason: &str| DurationError::Malformed {
+            src: input.to_string(),
+            reason: reason.to_string(),
+        };
+        let rest = input
+            .strip_prefix('P')
+            .ok_or_else(|| malformed("It must start with `P`"))?;
+        let (date, time) = match rest.split_once('T') {
+            Some((_, "")) => {
+                return Err(malformed("`T` must be followed by a time component").into());
+            }
+            Some((date, time)) => (date, time),
+            None if rest.is_empty() => {
+                return Err(malformed("It must have at least one component").into());
+            }
+            None => (rest, ""),
+        };
+        let seconds = sum_components(input, date, DATE_DESIGNATORS)?
+            .checked_add(sum_components(input, time, TIME_DESIGNATORS)?)
+            .ok_or_else(|| malformed("It does not fit in 64 bits of seconds"))?;
+        Ok(Self { seconds })
+    }
+}
+
+impl Add for Duration {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            seconds: self
+                .seconds
+                .checked_add(other.seconds)
+                .expect("Duration overflowed 64 bits of seconds"),
+        }
+    }
+}
+
+impl Sub for Duration {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            seconds: self
+                .seconds
+                .checked_sub(other.seconds)
+                .expect("Duration overflowed 64 bits of seconds"),
+        }
+    }
+}
+
+impl Neg for Duration {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            seconds: self
+                .seconds
+                .checked_neg()
+                .expect("Duration overflowed 64 bits of seconds"),
+        }
+    }
+}
+
+/// Adds up the `<number><designator>` pairs of one side of the `T`
+fn sum_components(
+    input: &str,
+    mut part: &str,
+    designators: &[(char, Option<i64>)],
+) -> Result<i64, Error> {
+    let malformed = |reason: String| DurationError::Malformed {
+        src: input.to_string(),
+        reason,
+    };
+    let mut designators = designators.iter();
+    let mut total: i64 = 0;
+    while !part.is_empty() {
+        let digits = part
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| malformed(format!("`{part}` is missing its designator")))?;
+        let designator = part[digits..].chars().next().expect("Found above");
+        if digits == 0 {
+            return Err(malformed(format!("`{designator}` must follow a number")).into());
+        }
+        // Consuming the iterator up to the match enforces order and uniqueness
+        let (_, length) = designators
+            .find(|(allowed, _)| *allowed == designator)
+            .ok_or_else(|| malformed(format!("`{designator}` is out of place")))?;
+        let length = length.ok_or_else(|| DurationError::CalendarComponent {
+            src: input.to_string(),
+            designator,
+        })?;
+        total = part[..digits]
+            .parse::<i64>()
+            .ok()
+            .and_then(|value| value.checked_mul(length))
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(|| malformed("It does not fit in 64 bits of seconds".to_string()))?;
+        part = &part[digits + designator.len_utf8()..];
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Error>;
+
+    #[test]
+    fn parse_iso8601_components() -> TestResult {
+        assert_eq!(Duration::parse_iso8601("PT90M")?.as_seconds(), 5400);
+        assert_eq!(Duration::parse_iso8601("P1D")?.as_seconds(), 86_400);
+        assert_eq!(Duration::parse_iso8601("PT2H")?.as_seconds(), 7200);
+        assert_eq!(Duration::parse_iso8601("PT45S")?.as_seconds(), 45);
+        assert_eq!(
+            Duration::parse_iso8601("P1DT2H30M")?.as_seconds(),
+            86_400 + 2 * 3600 + 30 * 60
+        );
+        assert_eq!(
+            Duration::parse_iso8601("P2W3DT1H1M1S")?.as_seconds(),
+            17 * 86_400 + 3661
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_iso8601_invalid() {
+        for input in [
+            "", "1D", "P", "PT", "P1", "PD", "PT1H2H", "PT1M1H", "P1H", "PT1D", "P1.5D",
+        ] {
+            assert!(
+                Duration::parse_iso8601(input).is_err(),
+                "Failed on input: {input}"
+            );
+        }
+
+        // Years and months are calendar dependent
+        for input in ["P1Y", "P2M", "P1Y2DT3H"] {
+            let report = Duration::parse_iso8601(input).expect_err("Calendar component");
+            assert!(matches!(
+                report.downcast_ref::<DurationError>(),
+                Some(DurationError::CalendarComponent { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn constructors() {
+        assert_eq!(Duration::seconds(-5).as_seconds(), -5);
+        assert_eq!(Duration::minutes(2).as_seconds(), 120);
+        assert_eq!(Duration::hours(3).as_seconds(), 10_800);
+        assert_eq!(Duration::days(-1).as_seconds(), -86_400);
+        assert_eq!(Duration::days(7), Duration::parse_iso8601("P1W").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "overflowed")]
+    fn constructor_overflow() {
+        Duration::days(i64::MAX / 1000);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(
+            (Duration::hours(2) + Duration::minutes(30)).as_seconds(),
+            9000
+        );
+        assert_eq!(
+            Duration::hours(1) - Duration::minutes(90),
+            Duration::minutes(-30)
+        );
+        assert_eq!(-Duration::seconds(-42), Duration::seconds(42));
+        assert_eq!(-Duration::seconds(42), Duration::seconds(-42));
+        assert_eq!(Duration::minutes(-3).abs(), Duration::minutes(3));
+        assert_eq!(Duration::minutes(3).abs(), Duration::minutes(3));
+        assert_eq!(Duration::default() - Duration::days(1), -Duration::days(1));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::datetime::{Datetime, DatetimeError};
+use crate::lexer::{Padding, Token};
+use crate::locale::Locale;
+use std::fmt::Write;
+
+use miette::Error;
+
+/// Looks up the name of a month, from 1 for January, in `names`
+fn month_name<'a>(names: &[&'a str; 12], month: usize) -> Result<&'a str, Error> {
+    month
+        .checked_sub(1)
+        .and_then(|index| names.get(index))
+        .copied()
+        .ok_or_else(|| {
+            DatetimeError::InvalidValue {
+                expected: "A month between 1-12".to_string(),
+                field: Token::WrittenMonth,
+                got: month.to_string(),
+                src: None,
+            }
+            .into()
+        })
+}
+
+/// Value of a numeric token, as in [`Token::numeric_width`]
+fn number(datetime: &Datetime, token: &Token) -> i64 {
+    match token {
+        Token::FullYear => datetime.year as i64,
+        Token::HalfYear => (datetime.year % 100) as i64,
+        Token::IsoYear => i64::from(datetime.iso_year_week().0),
+        Token::IsoWeek => i64::from(datetime.iso_year_week().1),
+        Token::IsoWeekdayNumber => datetime.iso_weekday() as i64,
+        Token::WeekdayNumber => (datetime.iso_weekday() % 7) as i64,
+        Token::MondayWeek => i64::from(datetime.week_of_year(true)),
+        Token::SundayWeek => i64::from(datetime.week_of_year(false)),
+        Token::FullMonth => datetime.month as i64,
+        Token::Day => datetime.day as i64,
+        Token::DayOfYear => i64::from(datetime.day_of_year()),
+        Token::TwentyFourHourDay | Token::Hour => datetime.hour as i64,
+        Token::TwelveHourDay => match datetime.hour % 12 {
+            0 => 12,
+            hour => hour as i64,
+        },
+        Token::Minute => datetime.minute as i64,
+        Token::Second => datetime.second as i64,
+        token => unreachable!("{token:?} is not a number"),
+    }
+}
+/// Appends `datetime` to `out`, laid out as `tokens` describe. Numbers
+/// are zero padded to the width they are parsed with, unless a flag or a
+/// width like `%-d` or `%4H` says otherwise. Optional groups are always
+/// written, and of a group of alternatives only the first. Literals are
+/// copied as lexed, so `%%` gives a single `%`.
+pub(crate) fn write_tokens(
+    out: &mut String,
+    datetime: &Datetime,
+    tokens: &[Token],
+    locale: &Locale,
+) -> Result<(), Error> {
+    // Writing to a `String` cannot fail
+    let mut tokens = tokens.iter();
+    while let Some(token) = tokens.next() {
+        let _ = match token {
+            // Fewer digits than nanoseconds cut the fraction, more add zeros
+            Token::Padded { width, token, .. } if **token == Token::Fraction => {
+                let digits = width.unwrap_or(9);
+                let fraction = format!("{:09}", datetime.nanosecond);
+                write!(out, "{:0<digits$}", &fraction[..digits.min(9)])
+            }
+            Token::Padded { padding, .. } => {
+                let width = token.numeric_width().expect("Only numbers are padded");
+                let Token::Padded { token, .. } = token else {
+                    unreachable!("Matched above")
+                };
+                let number = number(datetime, token);
+                match padding {
+                    Padding::Zero => write!(out, "{number:0width$}"),
+                    Padding::Space => write!(out, "{number:width$}"),
+                    Padding::Unpadded => write!(out, "{number}"),
+                }
+            }
+            token if token.numeric_width().is_some() => {
+                let width = token.numeric_width().expect("Checked above");
+                write!(out, "{:0width$}", number(datetime, token))
+            }
+            Token::WrittenMonth => out.write_str(month_name(&locale.months, datetime.month)?),
+            Token::ShortMonth => out.write_str(month_name(&locale.short_months, datetime.month)?),
+            Token::FullWeekday => out.write_str(locale.weekdays[datetime.iso_weekday() - 1]),
+            Token::ShortWeekday => out.write_str(locale.short_weekdays[datetime.iso_weekday() - 1]),
+            Token::Fraction => write!(out, "{:09}", datetime.nanosecond),
+            Token::Epoch => write!(out, "{}", datetime.checked_timestamp()?),
+            Token::Literal { pattern } => out.write_str(pattern),
+            Token::AmOrPm => out.write_str(if datetime.hour < 12 {
+                locale.am
+            } else {
+                locale.pm
+            }),
+            // An unknown offset is written the RFC 3339 way, as `-0000`
+            Token::UtcOffset => match datetime.offset_minutes {
+                Some(offset) => {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    let offset = offset.unsigned_abs();
+                    write!(out, "{sign}{:02}{:02}", offset / 60, offset % 60)
+                }
+                None => out.write_str("-0000"),
+            },
+            Token::TimeZoneName if let Some(zone) = datetime.zone_abbreviation => {
+                out.write_str(zone.name)
+            }
+            Token::TimeZoneName | Token::Custom(_) => {
+                return Err(DatetimeError::Unformattable {
+                    field: token.clone(),
+                }
+                .into());
+            }
+            Token::LocaleDateTime => {
+                write_tokens(out, datetime, &locale.date_time_tokens()?, locale)?;
+                Ok(())
+            }
+            // Only the first alternative is written, so the end of it skips
+            // the rest of the group
+            Token::Alternative => {
+                let mut depth = 0;
+                for token in tokens.by_ref() {
+                    match token {
+                        Token::AlternationStart => depth += 1,
+                        Token::AlternationEnd if depth == 0 => break,
+                        Token::AlternationEnd => depth -= 1,
+                        _ => {}
+                    }
+                }
+                Ok(())
+            }
+            Token::OptionalStart
+            | Token::OptionalEnd
+            | Token::AlternationStart
+            | Token::AlternationEnd => Ok(()),
+            token => unreachable!("{token:?} is a number"),
+        };
+    }
+    Ok(())
+}
@@ -1,10 +1,19 @@
-use crate::datetime::{Datetime, DatetimeBuilder, DatetimeError};
-use crate::lexer::{DateTimeLexer, Token};
-use miette::{Diagnostic, Error, IntoDiagnostic};
+use crate::datetime::{Datetime, DatetimeBuilder, DatetimeError, Weekday};
+use crate::lexer::{DateTimeLexer, Padding, Token};
+use crate::locale::Locale;
+use crate::options::{DEFAULT_CENTURY_PIVOT, FieldUpdate, ParseOptions, Strictness, Warning};
+use crate::timezone::{LocalResult, ZoneAbbreviation};
+use std::str::FromStr;
+
+use miette::{Diagnostic, Error, IntoDiagnostic, SourceSpan};
 use thiserror::Error;
 
-#[derive(Default)]
-pub(crate) struct Interpreter;
+/// Matches inputs against lexed formats
+pub(crate) struct Interpreter<'a> {
+    /// The whole input, for error reporting
+    src: &'a str,
+    options: &'a ParseOptions,
+}
 #[derive(Debug, Error, Diagnostic)]
 pub(crate) enum InterpreterError {
     #[error("Unexpect sequence. Expected `{}`, got `{}`", expected, unexpected)]
@@ -13,6 +22,10 @@ pub(crate) enum InterpreterError {
         unexpected: String,
         #[source_code]
         src: String,
+        /// The part of the format that was still to be matched, starting
+        /// at the token that failed
+        #[help]
+        remaining_format: Option<String>,
     },
     #[error(
         "Input sequence too short for token. Expected at least `{}` tokens but got `{}`",
@@ -25,8 +38,91 @@ pub(crate) enum InterpreterError {
         #[source_code]
         src: String,
     },
+    #[error("`{}` does not match the date, which falls on a {}", named, actual)]
+    WeekdayMismatch {
+        named: String,
+        actual: String,
+        #[source_code]
+        src: String,
+    },
+    #[error("`{}` is the start of more than one name: {}", prefix, candidates)]
+    AmbiguousName {
+        prefix: String,
+        candidates: String,
+        #[source_code]
+        src: String,
+    },
+    #[error("The week number `{}` needs a year", specifier)]
+    WeekWithoutYear {
+        specifier: &'static str,
+        #[source_code]
+        src: String,
+        #[help]
+        help: String,
+    },
+    #[error("Could not find the offset of the time zone `{}`", name)]
+    UnknownTimeZone {
+        name: String,
+        #[source_code]
+        src: String,
+        #[help]
+        help: Option<String>,
+    },
+    #[error(
+        "The local time is repeated in `{}`, with offsets {} and {}",
+        name,
+        earliest,
+        latest
+    )]
+    #[diagnostic(help("Add `%z` to the format to tell which of the two is meant"))]
+    AmbiguousLocalTime {
+        name: String,
+        earliest: i32,
+        latest: i32,
+        #[source_code]
+        src: String,
+    },
+    #[error("The local time is skipped by a transition in `{}`", name)]
+    NonexistentLocalTime {
+        name: String,
+        #[source_code]
+        src: String,
+    },
+    #[error("The parser of `%{}` did not return the rest of its input", specifier)]
+    #[diagnostic(help("Return a suffix of the input the parser was given, e.g.: from `split_at`"))]
+    CustomRest {
+        specifier: char,
+        #[source_code]
+        src: String,
+    },
+    #[error("Unexpected character `{}` in the input", character)]
+    ControlCharacter {
+        character: String,
+        #[source_code]
+        src: String,
+        #[label("Control or invisible character")]
+        at: SourceSpan,
+    },
+    #[error("Input of {} bytes is longer than the limit of {}", length, limit)]
+    #[diagnostic(help("Raise `ParseOptions::max_input_len` if such inputs are expected"))]
+    InputTooLong { length: usize, limit: usize },
+    #[error("Unexpected trailing input `{}` after the end of the format", trailing)]
+    TrailingInput {
+        trailing: String,
+        #[source_code]
+        src: String,
+        #[label("Not matched by the format")]
+        at: SourceSpan,
+    },
 }
-fn parse_digits(input: &str, width: usize) -> Result<(usize, &str), miette::Report> {
+/// Parses the first `width` bytes of `input` as a number of type `T`,
+/// returning it along with the rest of the input. Signed targets accept a
+/// leading `-`, which counts towards `width`.
+fn parse_number<T>(input: &str, width: usize) -> Result<(T, &str), miette::Report>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
     if input.len() < width {
         return Err(InterpreterError::InputTooShort {
             expected: width,
@@ -35,106 +131,1097 @@ fn parse_digits(input: &str, width: usize) -> Result<(usize, &str), miette::Repo
         }
         .into());
     }
-    let (part, rest) = input.split_at(width);
-    let number = part.parse::<usize>().into_diagnostic()?;
+    // A multi byte character straddling `width` can't be part of a number
+    let Some((part, rest)) = input.split_at_checked(width) else {
+        return Err(InterpreterError::WrongSequence {
+            expected: format!("{width} digits"),
+            unexpected: input.chars().take(width).collect(),
+            src: input.to_string(),
+            remaining_format: None,
+        }
+        .into());
+    };
+    let number = part.parse::<T>().into_diagnostic()?;
     Ok((number, rest))
 }
-impl Interpreter {
-    pub(crate) fn parse_datetime(
-        mut input: &str,
+/// Months as Roman numerals, matched by `%m` with
+/// [`ParseOptions::roman_months`]
+const ROMAN_MONTHS: [&str; 12] = [
+    "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII",
+];
+/// Zero width and byte order mark characters, which are not control
+/// characters but are just as invisible
+fn is_invisible(character: char) -> bool {
+    matches!(character, '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}')
+}
+/// Parses a number `width` digits wide with a padding flag. Zero padded
+/// numbers take all their digits, as without a flag. Otherwise up to that
+/// many digits are read, and space padded numbers may be preceded by spaces
+/// filling the rest of the field: `%e` reads ` 5`, `5` and `15`.
+fn parse_padded(
+    input: &str,
+    width: usize,
+    padding: Padding,
+) -> Result<(usize, &str), miette::Report> {
+    if padding == Padding::Zero {
+        return parse_number(input, width);
+    }
+    let mut digits = input;
+    if padding == Padding::Space {
+        let spaces = input
+            .bytes()
+            .take(width - 1)
+            .take_while(|&b| b == b' ')
+            .count();
+        digits = &input[spaces..];
+    }
+    let length = digits
+        .bytes()
+        .take(width)
+        .take_while(u8::is_ascii_digit)
+        .count();
+    parse_number(digits, length.max(1))
+}
+/// Places a two digit year in the hundred year window starting at
+/// `1900 + pivot`
+fn expand_half_year(year: usize, pivot: usize) -> usize {
+    if year < pivot {
+        year + 2000
+    } else {
+        year + 1900
+    }
+}
+/// Width in bytes of the input matched by `token`, when it is always the same
+fn fixed_width(token: &Token) -> Option<usize> {
+    match token {
+        Token::Literal { pattern } => Some(pattern.len()),
+        // Zero padded numbers take exactly their width, as in `%6Y`
+        Token::Padded {
+            padding: Padding::Zero,
+            token: inner,
+            ..
+        } if inner.numeric_width().is_some() => token.numeric_width(),
+        Token::Padded { .. } => None,
+        token => token.numeric_width(),
+    }
+}
+/// Rebuilds the format string that `tokens` were lexed from
+fn format_tokens(tokens: &[Token]) -> String {
+    tokens.iter().map(Token::as_format).collect()
+}
+/// Splits the tokens following an [`Token::OptionalStart`] into the
+/// contents of that group and whatever comes after its closing
+/// [`Token::OptionalEnd`]
+fn split_group(tokens: &[Token]) -> (&[Token], &[Token]) {
+    let (mut alternatives, rest) = split_alternatives(tokens);
+    (alternatives.remove(0), rest)
+}
+/// Splits the tokens following a group opening into the alternatives of
+/// that group and whatever comes after its closing token. Optional groups
+/// have a single alternative.
+fn split_alternatives(tokens: &[Token]) -> (Vec<&[Token]>, &[Token]) {
+    let mut alternatives = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    for (position, token) in tokens.iter().enumerate() {
+        match token {
+            Token::OptionalStart | Token::AlternationStart => depth += 1,
+            Token::Alternative if depth == 0 => {
+                alternatives.push(&tokens[start..position]);
+                start = position + 1;
+            }
+            Token::OptionalEnd | Token::AlternationEnd if depth == 0 => {
+                alternatives.push(&tokens[start..position]);
+                return (alternatives, &tokens[position + 1..]);
+            }
+            Token::OptionalEnd | Token::AlternationEnd => depth -= 1,
+            _ => {}
+        }
+    }
+    alternatives.push(&tokens[start..]);
+    (alternatives, &[])
+}
+/// Everything read from the input so far
+#[derive(Clone, Default)]
+struct Parsed {
+    datetime: DatetimeBuilder,
+    /// Weekday named by the input, from 0 for Monday. Checked against the
+    /// date once all of it is known.
+    weekday: Option<usize>,
+    /// Time zone named by the input, resolved once the date is known
+    zone: Option<String>,
+    /// The entry of [`ZoneAbbreviation::ALL`] the zone name matched
+    abbreviation: Option<ZoneAbbreviation>,
+    /// Whether `%Y` or `%y` were matched
+    has_year: bool,
+    /// Whether the hour, minute and second were matched
+    has_time: [bool; 3],
+    /// ISO 8601 week-numbering year and week, turned into a date once all
+    /// of the input is read
+    iso_year: Option<usize>,
+    iso_week: Option<usize>,
+    /// Week of the year from its first Monday (`%W`) or Sunday (`%U`),
+    /// along with whether it is the former
+    week: Option<(usize, bool)>,
+    /// Day of the year, turned into a month and day along with the year
+    day_of_year: Option<usize>,
+    /// What lenient parsing accepted so far
+    warnings: Vec<Warning>,
+}
+
+impl Parsed {
+    /// Notes a number that lenient parsing read with fewer digits than its
+    /// `width`, from `before` to `after` in the input
+    fn warn_unpadded(&mut self, token: &Token, before: &str, after: &str, width: usize) {
+        let got = &before[..before.len() - after.len()];
+        if got.len() < width {
+            self.warnings.push(Warning::Unpadded {
+                field: token.to_string(),
+                got: got.to_string(),
+            });
+        }
+    }
+    fn build(
+        mut self,
+        src: &str,
+        options: &ParseOptions,
+    ) -> Result<(Datetime, Vec<Warning>), Error> {
+        let locale = &options.locale;
+        let lenient = options.strictness == Strictness::Lenient;
+        let (has_year, year) = (self.has_year, self.datetime.year);
+        // Year that the week number of `specifier` belongs to, from the input
+        // if possible
+        let week_year = |week_year: Option<usize>, specifier: &'static str| match (
+            week_year,
+            has_year,
+            options.reference_year,
+        ) {
+            (Some(year), ..) => Ok(year),
+            (None, true, _) => Ok(year),
+            (None, false, Some(year)) => Ok(year),
+            _ => Err(InterpreterError::WeekWithoutYear {
+                specifier,
+                src: src.to_string(),
+                help: format!(
+                    "Add {} to the format, or set `ParseOptions::reference_year`",
+                    match specifier {
+                        "%V" => "`%G` or `%Y`",
+                        _ => "`%Y`",
+                    }
+                ),
+            }),
+        };
+        if let Some(week) = self.iso_week {
+            let year = week_year(self.iso_year, "%V")?;
+            let date = Datetime::from_iso_year_week(
+                i32::try_from(year).into_diagnostic()?,
+                u8::try_from(week).into_diagnostic()?,
+                Weekday::ALL[self.weekday.unwrap_or(0)],
+            )?;
+            self.datetime = self
+                .datetime
+                .year(date.year)
+                .month(date.month)
+                .day(date.day);
+        }
+        if let Some((week, monday_first)) = self.week {
+            let year = week_year(None, if monday_first { "%W" } else { "%U" })?;
+            // Without a weekday, the week's first day is taken
+            let weekday = match self.weekday {
+                Some(weekday) => Weekday::ALL[weekday],
+                None if monday_first => Weekday::Monday,
+                None => Weekday::Sunday,
+            };
+            let date = Datetime::from_year_week(
+                year,
+                u8::try_from(week).into_diagnostic()?,
+                weekday,
+                monday_first,
+            )?;
+            self.datetime = self
+                .datetime
+                .year(date.year)
+                .month(date.month)
+                .day(date.day);
+        }
+        if let Some(ordinal) = self.day_of_year {
+            let date = Datetime::checked_from_ordinal(
+                self.datetime.year,
+                u16::try_from(ordinal).into_diagnostic()?,
+            )?;
+            self.datetime = self.datetime.month(date.month).day(date.day);
+        }
+        if options.end_of_day {
+            let [hour, minute, second] = self.has_time;
+            if !hour {
+                self.datetime = self.datetime.hour(23);
+            }
+            if !minute {
+                self.datetime = self.datetime.minute(59);
+            }
+            if !second {
+                self.datetime = self.datetime.second(59);
+            }
+        }
+        if lenient {
+            let (month, day) = (self.datetime.month, self.datetime.day);
+            self.datetime = self.datetime.clamp_month().clamp_day();
+            if self.datetime.month != month {
+                self.warnings.push(Warning::MonthClamped { got: month });
+            }
+            if self.datetime.day != day {
+                self.warnings.push(Warning::DayClamped {
+                    got: day,
+                    last: self.datetime.day,
+                });
+            }
+        }
+        let mut datetime = self.datetime.clone().build()?;
+        // An explicit `%z` offset wins over the one of the zone, and the
+        // resolver over the built-in abbreviations
+        if let Some(name) = self.zone
+            && datetime.offset_minutes.is_none()
+        {
+            let unknown = |help: &str| InterpreterError::UnknownTimeZone {
+                name: name.clone(),
+                src: src.to_string(),
+                help: Some(help.to_string()),
+            };
+            let resolved = options
+                .tz_resolver
+                .as_ref()
+                .and_then(|resolver| resolver.resolve(&name, &datetime))
+                .or(self
+                    .abbreviation
+                    .map(|abbreviation| LocalResult::Unique(abbreviation.offset_minutes)));
+            let offset = match resolved {
+                None if options.tz_resolver.is_none() => {
+                    return Err(unknown(
+                        "Set `ParseOptions::tz_resolver` for zones other than `ZoneAbbreviation::ALL`",
+                    ).into());
+                }
+                None => return Err(unknown("The resolver does not know this zone").into()),
+                Some(LocalResult::Unique(offset)) => offset,
+                Some(LocalResult::Ambiguous { earliest, latest }) => {
+                    return Err(InterpreterError::AmbiguousLocalTime {
+                        name,
+                        earliest,
+                        latest,
+                        src: src.to_string(),
+                    }
+                    .into());
+                }
+                Some(LocalResult::Gap) => {
+                    return Err(InterpreterError::NonexistentLocalTime {
+                        name,
+                        src: src.to_string(),
+                    }
+                    .into());
+                }
+            };
+            datetime = self.datetime.offset_minutes(offset).build()?;
+        }
+        datetime.zone_abbreviation = self.abbreviation;
+        // A weekday next to a full date is redundant, so lenient parsing
+        // trusts the date when they disagree
+        if let Some(weekday) = self.weekday {
+            let actual = datetime.iso_weekday() - 1;
+            let (named, actual) = (locale.weekdays[weekday], locale.weekdays[actual]);
+            if named != actual && lenient {
+                self.warnings.push(Warning::WeekdayMismatch {
+                    named: named.to_string(),
+                    actual: actual.to_string(),
+                });
+            } else if named != actual {
+                return Err(InterpreterError::WeekdayMismatch {
+                    named: named.to_string(),
+                    actual: actual.to_string(),
+                    src: src.to_string(),
+                }
+                .into());
+            }
+        }
+        Ok((datetime, self.warnings))
+    }
+}
+/// A format that has already been lexed, to be matched against many inputs.
+/// Formats made only of fixed width numbers and literals, like
+/// `%Y%m%d%H%M%S`, are parsed by slicing the input bytes directly, with no
+/// allocations unless the input turns out to be invalid.
+/// # Examples
+/// ```
+/// use datetime::datetime::CompiledFormat;
+/// let format = CompiledFormat::new("%Y%m%d%H%M%S").unwrap();
+/// let date = format.parse("20240229134510").unwrap();
+/// assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledFormat {
+    tokens: Vec<Token>,
+    /// Length of every matching input, if the format has a fixed width
+    fixed_width: Option<usize>,
+}
+
+impl CompiledFormat {
+    pub fn new(format: &str) -> Result<Self, Error> {
+        let tokens = DateTimeLexer::new(format).collect::<Result<Vec<_>, _>>()?;
+        let fixed_width = tokens.iter().map(fixed_width).sum();
+        Ok(Self {
+            tokens,
+            fixed_width,
+        })
+    }
+    /// Parses `input`, with the same results as [`Datetime::from_str`]
+    pub fn parse(&self, input: &str) -> Result<Datetime, Error> {
+        if let Some(datetime) = self.parse_fixed_width(input) {
+            return Ok(datetime);
+        }
+        // Either the format is not fixed width or the input is invalid, in
+        // which case the generic path builds the detailed error
+        Interpreter::new(input, &ParseOptions::default()).parse(&self.tokens)
+    }
+    /// Parses every input in order, one result each. Valid inputs of fixed
+    /// width formats allocate nothing besides the returned vector, which
+    /// suits large columns of timestamps sharing a format.
+    /// # Examples
+    /// ```
+    /// use datetime::datetime::CompiledFormat;
+    /// let format = CompiledFormat::new("%Y-%m-%d").unwrap();
+    /// let dates = format.parse_column(&["2024-01-31", "2024-02-30"]);
+    /// assert_eq!(dates[0].as_ref().unwrap().day, 31);
+    /// assert!(dates[1].is_err());
+    /// ```
+    pub fn parse_column(&self, inputs: &[&str]) -> Vec<Result<Datetime, Error>> {
+        let mut column = Vec::with_capacity(inputs.len());
+        column.extend(inputs.iter().map(|input| self.parse(input)));
+        column
+    }
+    /// Fast path for fixed width formats. Returns `None` whenever the input
+    /// does not produce a valid date, leaving error reporting to the caller.
+    fn parse_fixed_width(&self, input: &str) -> Option<Datetime> {
+        if self.fixed_width? != input.len() {
+            return None;
+        }
+        let mut bytes = input.as_bytes();
+        let mut datetime = DatetimeBuilder::default();
+        for token in &self.tokens {
+            if let Token::Literal { pattern } = token {
+                bytes = bytes.strip_prefix(pattern.as_bytes())?;
+                continue;
+            }
+            let digits;
+            (digits, bytes) = bytes.split_at_checked(fixed_width(token)?)?;
+            // Wide fields may not fit, which the generic path reports
+            let number = digits.iter().try_fold(0, |number: usize, byte| {
+                let digit = byte.is_ascii_digit().then(|| usize::from(byte - b'0'))?;
+                number.checked_mul(10)?.checked_add(digit)
+            })?;
+            let token = match token {
+                Token::Padded { token, .. } => token,
+                token => token,
+            };
+            datetime = match token {
+                Token::FullYear => datetime.year(number),
+                Token::HalfYear => datetime.year(expand_half_year(number, DEFAULT_CENTURY_PIVOT)),
+                Token::FullMonth => datetime.month(number),
+                Token::Day => datetime.day(number),
+                Token::TwentyFourHourDay | Token::TwelveHourDay => datetime.hour(number),
+                Token::Minute => datetime.minute(number),
+                Token::Second => datetime.second(number),
+                _ => return None,
+            };
+        }
+        datetime.build().ok()
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(src: &'a str, options: &'a ParseOptions) -> Self {
+        let src = if options.trim_input {
+            src.trim_matches(|c: char| c.is_whitespace() || c == '\u{FEFF}')
+        } else {
+            src
+        };
+        Self { src, options }
+    }
+    pub(crate) fn parse_datetime(input: &str, expected_format: &str) -> Result<Datetime, Error> {
+        Interpreter::parse_datetime_with_options(input, expected_format, &ParseOptions::default())
+    }
+    pub(crate) fn parse_datetime_with_options(
+        input: &str,
         expected_format: &str,
+        options: &ParseOptions,
     ) -> Result<Datetime, Error> {
-        let lexer = DateTimeLexer::new(expected_format);
-        let original_input = input;
-        let mut datetime = DatetimeBuilder::default();
-        for token in lexer {
-            let token = token?;
+        let tokens = DateTimeLexer::new(expected_format)
+            .with_custom(
+                options
+                    .custom_specifiers
+                    .iter()
+                    .map(|(specifier, _)| *specifier),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        Interpreter::new(input, options).parse(&tokens)
+    }
+    pub(crate) fn parse_datetime_with_warnings(
+        input: &str,
+        expected_format: &str,
+        options: &ParseOptions,
+    ) -> Result<(Datetime, Vec<Warning>), Error> {
+        let tokens = DateTimeLexer::new(expected_format)
+            .with_custom(
+                options
+                    .custom_specifiers
+                    .iter()
+                    .map(|(specifier, _)| *specifier),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        Interpreter::new(input, options).parse_with_warnings(&tokens)
+    }
+    /// Matches the whole input against `tokens`. Inputs over the length
+    /// limit are rejected before reading them. Inputs with control or
+    /// invisible characters are rejected up front, as they can never be
+    /// part of a date and usually mean the input is corrupted. Whitespace
+    /// like tabs and newlines is left to the format.
+    fn parse(&self, tokens: &[Token]) -> Result<Datetime, Error> {
+        self.parse_with_warnings(tokens)
+            .map(|(datetime, _)| datetime)
+    }
+    /// Same as [`Interpreter::parse`], also returning what lenient parsing
+    /// accepted
+    fn parse_with_warnings(&self, tokens: &[Token]) -> Result<(Datetime, Vec<Warning>), Error> {
+        if let Some(limit) = self.options.max_input_len
+            && self.src.len() > limit
+        {
+            return Err(InterpreterError::InputTooLong {
+                length: self.src.len(),
+                limit,
+            }
+            .into());
+        }
+        if let Some((position, character)) = self
+            .src
+            .char_indices()
+            .find(|(_, c)| (c.is_control() && !c.is_whitespace()) || is_invisible(*c))
+        {
+            return Err(InterpreterError::ControlCharacter {
+                character: character.escape_unicode().to_string(),
+                src: self.src.to_string(),
+                at: (position, character.len_utf8()).into(),
+            }
+            .into());
+        }
+        self.parse_tokens(tokens, self.src, Parsed::default())?
+            .build(self.src, self.options)
+    }
+    fn lenient(&self) -> bool {
+        self.options.strictness == Strictness::Lenient
+    }
+    /// Removes `prefix` from the start of `input`, ignoring ASCII case if
+    /// the options ask for it
+    fn strip_prefix<'i>(&self, input: &'i str, prefix: &str) -> Option<&'i str> {
+        if !self.options.case_insensitive {
+            return input.strip_prefix(prefix);
+        }
+        let head = input.get(..prefix.len())?;
+        head.eq_ignore_ascii_case(prefix)
+            .then(|| &input[prefix.len()..])
+    }
+    /// Matches the longest of `names` at the start of `input`, returning
+    /// its position in `names` and the rest of the input
+    fn strip_name<'i>(&self, input: &'i str, names: &[&str]) -> Option<(usize, &'i str)> {
+        names
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| Some((index, self.strip_prefix(input, name)?)))
+            .min_by_key(|(_, rest)| rest.len())
+    }
+    /// The weekday whose full or abbreviated name starts with the letters
+    /// at the start of `input`, for truncated names like `Mond`. Errors if
+    /// those letters start the names of several weekdays, like `S`.
+    fn weekday_prefix<'i>(&self, input: &'i str) -> Result<Option<(usize, &'i str)>, Error> {
+        let length = input
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(input.len());
+        let (prefix, rest) = input.split_at(length);
+        if prefix.is_empty() {
+            return Ok(None);
+        }
+        let locale = &self.options.locale;
+        let matches = (0..7)
+            .filter(|&weekday| {
+                [locale.weekdays[weekday], locale.short_weekdays[weekday]]
+                    .iter()
+                    .any(|name| self.strip_prefix(name, prefix).is_some())
+            })
+            .collect::<Vec<_>>();
+        match matches.as_slice() {
+            [] => Ok(None),
+            [weekday] => Ok(Some((*weekday, rest))),
+            _ => Err(InterpreterError::AmbiguousName {
+                prefix: prefix.to_string(),
+                candidates: matches
+                    .iter()
+                    .map(|&weekday| locale.weekdays[weekday])
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                src: self.src.to_string(),
+            }
+            .into()),
+        }
+    }
+    /// A full or abbreviated month name at the start of `input`, in place of
+    /// a number. Only looked for when `input` starts with a letter.
+    fn month_name_for_number<'i>(&self, input: &'i str) -> Option<(usize, &'i str)> {
+        if !input.starts_with(char::is_alphabetic) {
+            return None;
+        }
+        let locale = &self.options.locale;
+        let (month, rest) = self
+            .strip_name(input, &locale.months)
+            .or_else(|| self.strip_name(input, &locale.short_months))?;
+        Some((month + 1, rest))
+    }
+    /// Error for a name, like a month's, that `input` does not start with
+    fn missing_name(&self, token: &Token, input: &str, remaining_format: Option<String>) -> Error {
+        let unexpected = input
+            .split(|c: char| !c.is_alphabetic())
+            .next()
+            .unwrap_or(input);
+        InterpreterError::WrongSequence {
+            expected: format!("{} name", token.to_string().to_lowercase()),
+            unexpected: unexpected.to_string(),
+            src: self.src.to_string(),
+            remaining_format,
+        }
+        .into()
+    }
+    /// Reads the digits after a decimal point as nanoseconds. Unless a
+    /// `width` asks for exactly that many, any number of digits is
+    /// accepted, as RFC 3339 allows, with those past nanoseconds dropped.
+    /// At least one is needed, so a decimal point with nothing after it, as
+    /// in `12:00:00.`, is an error rather than zero.
+    fn parse_fraction<'i>(
+        &self,
+        input: &'i str,
+        width: Option<usize>,
+        remaining_format: impl Fn() -> Option<String>,
+    ) -> Result<(usize, &'i str), Error> {
+        let length = input.bytes().take_while(u8::is_ascii_digit).count();
+        let length = match width {
+            Some(width) if length < width => 0,
+            Some(width) => width,
+            None => length,
+        };
+        if length == 0 {
+            let digits = width.map(|width| format!("{width} ")).unwrap_or_default();
+            return Err(InterpreterError::WrongSequence {
+                expected: format!("{digits}digits of a fraction of a second"),
+                unexpected: input.chars().take(1).collect(),
+                src: self.src.to_string(),
+                remaining_format: remaining_format(),
+            }
+            .into());
+        }
+        let (digits, rest) = input.split_at(length);
+        let nanosecond = digits
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |nanosecond, digit| {
+                nanosecond * 10 + usize::from(digit - b'0')
+            });
+        Ok((nanosecond, rest))
+    }
+    /// Reads an offset from UTC written as `+hh:mm`, `+hhmm`, `+hh` or `Z`
+    /// (in either case),
+    /// in minutes and east positive. Any number of minutes is kept as is,
+    /// for historical offsets like `+05:53`. `-00:00` gives `None`, as RFC
+    /// 3339 reserves it for an unknown local offset, while `+00:00` and `Z`
+    /// are UTC.
+    fn parse_utc_offset<'i>(
+        &self,
+        input: &'i str,
+        remaining_format: impl Fn() -> Option<String>,
+    ) -> Result<(Option<i32>, &'i str), Error> {
+        if let Some(rest) = input.strip_prefix(['Z', 'z']) {
+            return Ok((Some(0), rest));
+        }
+        let (negative, rest) = if let Some(rest) = input.strip_prefix('+') {
+            (false, rest)
+        } else if let Some(rest) = input.strip_prefix('-') {
+            (true, rest)
+        } else {
+            return Err(InterpreterError::WrongSequence {
+                expected: "+ or -".to_string(),
+                unexpected: input.chars().take(1).collect(),
+                src: self.src.to_string(),
+                remaining_format: remaining_format(),
+            }
+            .into());
+        };
+        let (hours, rest) = parse_number::<u32>(rest, 2)?;
+        let (minutes, rest) = match rest.strip_prefix(':') {
+            Some(rest) => parse_number::<u32>(rest, 2)?,
+            None if rest
+                .as_bytes()
+                .get(..2)
+                .is_some_and(|m| m.iter().all(u8::is_ascii_digit)) =>
+            {
+                parse_number::<u32>(rest, 2)?
+            }
+            None => (0, rest),
+        };
+        if hours > 23 || minutes > 59 {
+            return Err(DatetimeError::InvalidValue {
+                expected: "An offset within ±23:59".to_string(),
+                field: Token::UtcOffset,
+                got: input[..input.len() - rest.len()].to_string(),
+                src: Some(self.src.to_string()),
+            }
+            .into());
+        }
+        let offset = (hours * 60 + minutes) as i32;
+        Ok(match (negative, offset) {
+            (true, 0) => (None, rest),
+            (true, offset) => (Some(-offset), rest),
+            (false, offset) => (Some(offset), rest),
+        })
+    }
+    /// Saves the value of a numeric token
+    fn store_number(&self, parsed: &mut Parsed, token: &Token, number: usize) {
+        let datetime = parsed.datetime.clone();
+        parsed.datetime = match token {
+            Token::FullYear => datetime.year(number),
+            Token::HalfYear => datetime.year(expand_half_year(number, self.options.century_pivot)),
+            Token::FullMonth => datetime.month(number),
+            Token::Day => datetime.day(number),
+            Token::TwelveHourDay | Token::TwentyFourHourDay | Token::Hour => datetime.hour(number),
+            Token::Minute => datetime.minute(number),
+            Token::Second => datetime.second(number),
+            Token::IsoYear => {
+                parsed.iso_year = Some(number);
+                datetime
+            }
+            Token::IsoWeek => {
+                parsed.iso_week = Some(number);
+                datetime
+            }
+            Token::MondayWeek | Token::SundayWeek => {
+                parsed.week = Some((number, *token == Token::MondayWeek));
+                datetime
+            }
+            Token::DayOfYear => {
+                parsed.day_of_year = Some(number);
+                datetime
+            }
+            token => unreachable!("{token:?} is not a number"),
+        };
+        match token {
+            Token::FullYear | Token::HalfYear => parsed.has_year = true,
+            Token::TwelveHourDay | Token::TwentyFourHourDay | Token::Hour => {
+                parsed.has_time[0] = true
+            }
+            Token::Minute => parsed.has_time[1] = true,
+            Token::Second => parsed.has_time[2] = true,
+            _ => {}
+        }
+    }
+    /// Matches `input` against `tokens`, filling `parsed` along the way.
+    /// The whole input must be consumed. Optional groups are first tried
+    /// with their contents and, if anything after that fails, without them.
+    /// Alternatives are likewise tried from left to right.
+    fn parse_tokens(
+        &self,
+        tokens: &[Token],
+        mut input: &str,
+        mut parsed: Parsed,
+    ) -> Result<Parsed, Error> {
+        for (position, token) in tokens.iter().enumerate() {
+            let remaining_format = || Some(format_tokens(&tokens[position..]));
+            // Input that ends right before a separator leaves the rest of
+            // the format to its defaults, as with `12:00` for `%H:%M:%S`
+            if self.options.trailing_tolerant
+                && input.is_empty()
+                && !self.src.is_empty()
+                && matches!(token, Token::Literal { .. })
+            {
+                break;
+            }
             match token {
-                Token::FullYear => {
-                    let year: usize;
-                    (year, input) = parse_digits(input, 4)?;
-                    datetime = datetime.year(year)
+                Token::FullYear
+                | Token::HalfYear
+                | Token::IsoYear
+                | Token::IsoWeek
+                | Token::MondayWeek
+                | Token::SundayWeek
+                | Token::Day
+                | Token::DayOfYear
+                | Token::TwelveHourDay
+                | Token::TwentyFourHourDay
+                | Token::Hour
+                | Token::Minute
+                | Token::Second => {
+                    let width = token.numeric_width().expect("Numeric token");
+                    let number;
+                    // Years keep all their digits, so `24` isn't year 24
+                    if self.lenient() && width == 2 {
+                        let before = input;
+                        (number, input) = parse_padded(input, width, Padding::Unpadded)?;
+                        parsed.warn_unpadded(token, before, input, width);
+                    } else {
+                        (number, input) = parse_number(input, width)?;
+                    }
+                    self.store_number(&mut parsed, token, number);
                 }
-                Token::HalfYear => {
-                    let y: usize;
-                    (y, input) = parse_digits(input, 2)?;
-                    datetime = datetime.year(if y < 25 { y + 2000 } else { y + 1900 });
+                Token::Padded { width, token, .. } if **token == Token::Fraction => {
+                    let nanosecond;
+                    (nanosecond, input) = self.parse_fraction(input, *width, remaining_format)?;
+                    parsed.datetime = parsed.datetime.nanosecond(nanosecond);
+                }
+                Token::Padded { padding, .. } => {
+                    let width = token.numeric_width().expect("Only numbers are padded");
+                    let Token::Padded { token, .. } = token else {
+                        unreachable!("Matched above")
+                    };
+                    let number;
+                    (number, input) = parse_padded(input, width, *padding)?;
+                    self.store_number(&mut parsed, token, number);
                 }
                 Token::FullMonth => {
                     let mes: usize;
-                    (mes, input) = parse_digits(input, 2)?;
-                    datetime = datetime.month(mes);
+                    let roman = || {
+                        let (month, rest) = self.strip_name(input, &ROMAN_MONTHS)?;
+                        Some((month + 1, rest))
+                    };
+                    let before = input;
+                    (mes, input) = match self.month_name_for_number(input) {
+                        Some((month, rest)) if self.lenient() => {
+                            parsed.warnings.push(Warning::MonthName {
+                                name: before[..before.len() - rest.len()].to_string(),
+                            });
+                            (month, rest)
+                        }
+                        _ if self.options.roman_months
+                            && let Some(found) = roman() =>
+                        {
+                            found
+                        }
+                        _ if self.lenient() => {
+                            let (month, rest) = parse_padded(input, 2, Padding::Unpadded)?;
+                            parsed.warn_unpadded(token, before, rest, 2);
+                            (month, rest)
+                        }
+                        _ => parse_number(input, 2)?,
+                    };
+                    self.store_number(&mut parsed, token, mes);
                 }
-                Token::Day => {
-                    let day: usize;
-                    (day, input) = parse_digits(input, 2)?;
-                    datetime = datetime.day(day);
+                Token::WrittenMonth | Token::ShortMonth => {
+                    let names = match token {
+                        Token::WrittenMonth => &self.options.locale.months,
+                        _ => &self.options.locale.short_months,
+                    };
+                    let Some((month, rest)) = self.strip_name(input, names) else {
+                        return Err(self.missing_name(token, input, remaining_format()));
+                    };
+                    input = rest;
+                    parsed.datetime = parsed.datetime.month(month + 1);
                 }
-                Token::TwelveHourDay | Token::TwentyFourHourDay => {
-                    let hour: usize;
-                    (hour, input) = parse_digits(input, 2)?;
-                    datetime = datetime.hour(hour);
+                // Numbered weekdays are checked against the date like names
+                Token::IsoWeekdayNumber | Token::WeekdayNumber => {
+                    let number: usize;
+                    (number, input) = parse_number(input, 1)?;
+                    let weekday = match (token, number) {
+                        (Token::IsoWeekdayNumber, 1..=7) => number - 1,
+                        (Token::WeekdayNumber, 0..=6) => (number + 6) % 7,
+                        _ => {
+                            return Err(DatetimeError::InvalidValue {
+                                expected: match token {
+                                    Token::IsoWeekdayNumber => "1-7, from Monday",
+                                    _ => "0-6, from Sunday",
+                                }
+                                .to_string(),
+                                field: token.clone(),
+                                got: number.to_string(),
+                                src: Some(self.src.to_string()),
+                            }
+                            .into());
+                        }
+                    };
+                    parsed.weekday = Some(weekday);
+                }
+                Token::FullWeekday | Token::ShortWeekday => {
+                    let names = match token {
+                        Token::FullWeekday => &self.options.locale.weekdays,
+                        _ => &self.options.locale.short_weekdays,
+                    };
+                    let found = if self.lenient() {
+                        self.weekday_prefix(input)?
+                    } else {
+                        self.strip_name(input, names)
+                    };
+                    let Some((weekday, rest)) = found else {
+                        return Err(self.missing_name(token, input, remaining_format()));
+                    };
+                    let read = &input[..input.len() - rest.len()];
+                    let locale = &self.options.locale;
+                    let full_name = [locale.weekdays[weekday], locale.short_weekdays[weekday]]
+                        .iter()
+                        .any(|name| self.strip_prefix(read, name) == Some(""));
+                    if !full_name {
+                        parsed.warnings.push(Warning::WeekdayPrefix {
+                            prefix: read.to_string(),
+                            weekday: locale.weekdays[weekday].to_string(),
+                        });
+                    }
+                    input = rest;
+                    parsed.weekday = Some(weekday);
                 }
                 Token::AmOrPm => {
-                    let hour = datetime.hour;
-                    if input.starts_with("PM") {
-                        input = &input[2..];
+                    let hour = parsed.datetime.hour;
+                    let Locale { am, pm, .. } = self.options.locale;
+                    if let Some(rest) = self.strip_prefix(input, pm) {
+                        input = rest;
                         if hour < 12 {
-                            datetime = datetime.hour(hour + 12);
+                            parsed.datetime = parsed.datetime.hour(hour + 12);
                         }
-                    } else if input.starts_with("AM") {
-                        input = &input[2..];
+                    } else if let Some(rest) = self.strip_prefix(input, am) {
+                        input = rest;
                         if hour == 12 {
-                            datetime = datetime.hour(0);
+                            parsed.datetime = parsed.datetime.hour(0);
                         }
                     } else {
                         return Err(InterpreterError::WrongSequence {
-                            expected: "AM or PM".to_string(),
-                            unexpected: input.get(..2).unwrap_or(input).to_string(),
-                            src: original_input.to_string(),
+                            expected: format!("{am} or {pm}"),
+                            unexpected: input.get(..pm.len()).unwrap_or(input).to_string(),
+                            src: self.src.to_string(),
+                            remaining_format: remaining_format(),
                         }
                         .into());
                     }
                 }
-                Token::Minute => {
-                    let minute: usize;
-                    (minute, input) = parse_digits(input, 2)?;
-                    datetime = datetime.minute(minute)
+                // A fraction right after another field, as in `%S%f`, brings
+                // its own `.` or `,`, and may be missing altogether. After a
+                // separator of the format, or its own, digits must follow.
+                Token::Fraction => {
+                    let after_literal =
+                        matches!(tokens[..position].last(), Some(Token::Literal { .. }));
+                    let separated = input.strip_prefix(['.', ',']).filter(|_| !after_literal);
+                    let nanosecond;
+                    (nanosecond, input) = match separated {
+                        Some(rest) => self.parse_fraction(rest, None, remaining_format)?,
+                        None if after_literal
+                            || input.starts_with(|c: char| c.is_ascii_digit()) =>
+                        {
+                            self.parse_fraction(input, None, remaining_format)?
+                        }
+                        None => (0, input),
+                    };
+                    parsed.datetime = parsed.datetime.nanosecond(nanosecond);
+                }
+                // Seconds since the epoch, possibly negative and with a
+                // fraction, setting every field at once
+                Token::Epoch => {
+                    let negative = input.starts_with('-');
+                    let sign = usize::from(negative);
+                    let length =
+                        sign + input[sign..].bytes().take_while(u8::is_ascii_digit).count();
+                    if length == sign {
+                        return Err(InterpreterError::WrongSequence {
+                            expected: "digits of a Unix timestamp".to_string(),
+                            unexpected: input.chars().take(1).collect(),
+                            src: self.src.to_string(),
+                            remaining_format: remaining_format(),
+                        }
+                        .into());
+                    }
+                    let digits;
+                    (digits, input) = input.split_at(length);
+                    let out_of_range = || DatetimeError::OutOfRange {
+                        operation: format!("Unix timestamp {digits}"),
+                    };
+                    let mut seconds: i64 = digits.parse().map_err(|_| out_of_range())?;
+                    let mut nanosecond = 0;
+                    if let Some(rest) = input.strip_prefix('.') {
+                        (nanosecond, input) = self.parse_fraction(rest, None, remaining_format)?;
+                    }
+                    // The fraction of a negative timestamp counts backwards
+                    if negative && nanosecond > 0 {
+                        seconds = seconds.checked_sub(1).ok_or_else(out_of_range)?;
+                        nanosecond = 1_000_000_000 - nanosecond;
+                    }
+                    let date = Datetime::from_unix_timestamp(seconds)?;
+                    parsed.datetime = parsed
+                        .datetime
+                        .year(date.year)
+                        .month(date.month)
+                        .day(date.day)
+                        .hour(date.hour)
+                        .minute(date.minute)
+                        .second(date.second)
+                        .nanosecond(nanosecond);
+                    parsed.has_year = true;
+                    parsed.has_time = [true; 3];
                 }
-                Token::Second => {
-                    let second: usize;
-                    (second, input) = parse_digits(input, 2)?;
-                    datetime = datetime.second(second)
+                // Any run of whitespace stands for whitespace in the format
+                Token::Literal { pattern }
+                    if self.lenient()
+                        && pattern.chars().all(char::is_whitespace)
+                        && input.starts_with(char::is_whitespace) =>
+                {
+                    let rest = input.trim_start();
+                    let got = &input[..input.len() - rest.len()];
+                    if got != pattern {
+                        parsed.warnings.push(Warning::Whitespace {
+                            got: got.to_string(),
+                        });
+                    }
+                    input = rest;
                 }
                 Token::Literal { pattern } => {
-                    if let Some(rest) = input.strip_prefix(&pattern) {
+                    if let Some(rest) = self.strip_prefix(input, pattern) {
                         input = rest;
+                        // Doubled separators like `2024--01` are mistakes
+                        // whose intent is clear
+                        if self.lenient() && pattern.chars().count() == 1 {
+                            let before = input;
+                            while let Some(rest) = self.strip_prefix(input, pattern) {
+                                input = rest;
+                            }
+                            if input.len() < before.len() {
+                                parsed.warnings.push(Warning::RepeatedSeparator {
+                                    separator: pattern.clone(),
+                                });
+                            }
+                        }
                     } else {
                         return Err(InterpreterError::WrongSequence {
                             unexpected: input.get(..pattern.len()).unwrap_or(input).to_string(),
-                            expected: pattern,
-                            src: original_input.to_string(),
+                            expected: pattern.clone(),
+                            src: self.src.to_string(),
+                            remaining_format: remaining_format(),
                         }
                         .into());
                     }
                 }
-                token => {
-                    todo!("{token:?} not yet implemented")
+                Token::OptionalStart => {
+                    let (group, rest) = split_group(&tokens[position + 1..]);
+                    let with_group = [group, rest].concat();
+                    return self
+                        .parse_tokens(&with_group, input, parsed.clone())
+                        .or_else(|_| self.parse_tokens(rest, input, parsed));
+                }
+                // Each alternative is tried in turn, followed by the rest of
+                // the format, and the first to match wins
+                Token::AlternationStart => {
+                    let (alternatives, rest) = split_alternatives(&tokens[position + 1..]);
+                    let mut error = None;
+                    for alternative in alternatives {
+                        let with_alternative = [alternative, rest].concat();
+                        match self.parse_tokens(&with_alternative, input, parsed.clone()) {
+                            Ok(parsed) => return Ok(parsed),
+                            Err(report) => error = Some(report),
+                        }
+                    }
+                    return Err(error.expect("At least one alternative"));
+                }
+                // Groups are flattened into their surroundings when entered
+                Token::OptionalEnd | Token::Alternative | Token::AlternationEnd => {}
+                Token::UtcOffset => {
+                    let offset;
+                    (offset, input) = self.parse_utc_offset(input, remaining_format)?;
+                    if let Some(offset) = offset {
+                        parsed.datetime = parsed.datetime.offset_minutes(offset);
+                    }
+                }
+                // Zone names run until a character that can't be in one
+                Token::TimeZoneName => {
+                    let length = input
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || "/_+-".contains(c)))
+                        .unwrap_or(input.len());
+                    if length == 0 {
+                        return Err(self.missing_name(token, input, remaining_format()));
+                    }
+                    let name;
+                    (name, input) = input.split_at(length);
+                    parsed.abbreviation = ZoneAbbreviation::ALL
+                        .iter()
+                        .find(|abbreviation| self.strip_prefix(name, abbreviation.name) == Some(""))
+                        .copied();
+                    parsed.zone = Some(name.to_string());
+                }
+                Token::LocaleDateTime => {
+                    let expanded = self.options.locale.date_time_tokens()?;
+                    let with_expansion = [&expanded, &tokens[position + 1..]].concat();
+                    return self.parse_tokens(&with_expansion, input, parsed);
                 }
+                // The last parser registered for a specifier wins
+                Token::Custom(specifier) => {
+                    let (_, parser) = self
+                        .options
+                        .custom_specifiers
+                        .iter()
+                        .rfind(|(registered, _)| registered == specifier)
+                        .expect("Only registered specifiers are lexed as custom");
+                    let (update, rest) = parser(input)?;
+                    // Offsets into the input are taken from what is left
+                    if rest.len() > input.len() || !input.ends_with(rest) {
+                        return Err(InterpreterError::CustomRest {
+                            specifier: *specifier,
+                            src: self.src.to_string(),
+                        }
+                        .into());
+                    }
+                    input = &input[input.len() - rest.len()..];
+                    match update {
+                        FieldUpdate::Year(year) => {
+                            self.store_number(&mut parsed, &Token::FullYear, year)
+                        }
+                        FieldUpdate::Month(month) => {
+                            self.store_number(&mut parsed, &Token::FullMonth, month)
+                        }
+                        FieldUpdate::Day(day) => self.store_number(&mut parsed, &Token::Day, day),
+                        FieldUpdate::Hour(hour) => {
+                            self.store_number(&mut parsed, &Token::Hour, hour)
+                        }
+                        FieldUpdate::Minute(minute) => {
+                            self.store_number(&mut parsed, &Token::Minute, minute)
+                        }
+                        FieldUpdate::Second(second) => {
+                            self.store_number(&mut parsed, &Token::Second, second)
+                        }
+                        FieldUpdate::Nanosecond(nanosecond) => {
+                            parsed.datetime = parsed.datetime.nanosecond(nanosecond)
+                        }
+                        FieldUpdate::Nothing => {}
+                    }
+                }
+            }
+        }
+        if !input.is_empty() {
+            let consumed = self.src.len() - input.len();
+            return Err(InterpreterError::TrailingInput {
+                trailing: input.to_string(),
+                src: self.src.to_string(),
+                at: (consumed, input.len()).into(),
             }
+            .into());
         }
-        datetime.build()
+        Ok(parsed)
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lexer::LexerError;
+    use crate::timezone::TzResolver;
+    use std::sync::Arc;
     type TestResult = Result<(), miette::Error>;
 
     #[test]
     fn basic_str_to_datetime() -> TestResult {
-        let mut input = String::from("04-02-2003");
-        let result = Interpreter::parse_datetime(&mut input, "%d-%m-%Y")?;
+        let input = String::from("04-02-2003");
+        let result = Interpreter::parse_datetime(&input, "%d-%m-%Y")?;
         assert_eq!(
             result,
             Datetime {
@@ -148,21 +1235,21 @@ mod tests {
     }
     #[test]
     fn expected_err() -> TestResult {
-        let mut input = String::from("04-02?2003");
-        let result = Interpreter::parse_datetime(&mut input, "%d-%m-%Y");
+        let input = String::from("04-02?2003");
+        let result = Interpreter::parse_datetime(&input, "%d-%m-%Y");
         assert!(result.is_err());
         Ok(())
     }
     #[test]
     fn test_all_token_types() -> TestResult {
         // Test year parsing
-        let mut input = String::from("2023");
-        let result = parse_digits(&mut input, 4)?;
+        let input = String::from("2023");
+        let result = parse_number::<usize>(&input, 4)?;
         assert_eq!(result.0, 2023);
 
         // Test full datetime with all components
-        let mut input = String::from("2023-05-15 14:30:25");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d %H:%M:%S")?;
+        let input = String::from("2023-05-15 14:30:25");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d %H:%M:%S")?;
         assert_eq!(
             result,
             Datetime {
@@ -172,12 +1259,15 @@ mod tests {
                 hour: 14,
                 minute: 30,
                 second: 25,
+                nanosecond: 0,
+                offset_minutes: None,
+                zone_abbreviation: None,
             }
         );
 
         // Test AM/PM format
-        let mut input = String::from("03:45:20 PM");
-        let result = Interpreter::parse_datetime(&mut input, "%I:%M:%S %p")?;
+        let input = String::from("03:45:20 PM");
+        let result = Interpreter::parse_datetime(&input, "%I:%M:%S %p")?;
         assert_eq!(
             result,
             Datetime {
@@ -191,21 +1281,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn two_digit_year_at_the_end() -> TestResult {
+        for (century_pivot, year) in [(25, 1999), (100, 2099)] {
+            let options = ParseOptions {
+                century_pivot,
+                ..ParseOptions::default()
+            };
+            let result =
+                Interpreter::parse_datetime_with_options("31/12/99", "%d/%m/%y", &options)?;
+            assert_eq!(
+                (result.year, result.month, result.day),
+                (year, 12, 31),
+                "Failed on pivot {century_pivot}"
+            );
+        }
+        let result = Interpreter::parse_datetime("31/12/24", "%d/%m/%y")?;
+        assert_eq!(result.year, 2024);
+        Ok(())
+    }
+
     #[test]
     fn test_error_handling() -> TestResult {
         // Test mismatched literals
-        let mut input = String::from("2023/05/15");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d");
+        let input = String::from("2023/05/15");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d");
         assert!(result.is_err());
 
         // Test insufficient digits
-        let mut input = String::from("23-5-15");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d");
+        let input = String::from("23-5-15");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d");
         assert!(result.is_err());
 
         // Test invalid numbers
-        let mut input = String::from("20a3-05-15");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d");
+        let input = String::from("20a3-05-15");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d");
         assert!(result.is_err());
 
         Ok(())
@@ -214,19 +1324,1199 @@ mod tests {
     #[test]
     fn test_edge_dates() -> TestResult {
         // Test minimum date
-        let mut input = String::from("0001-01-01");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d")?;
+        let input = String::from("0001-01-01");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d")?;
         assert_eq!(result.year, 1);
         assert_eq!(result.month, 1);
         assert_eq!(result.day, 1);
 
         // Test leap year date
-        let mut input = String::from("2020-02-29");
-        let result = Interpreter::parse_datetime(&mut input, "%Y-%m-%d")?;
+        let input = String::from("2020-02-29");
+        let result = Interpreter::parse_datetime(&input, "%Y-%m-%d")?;
         assert_eq!(result.year, 2020);
         assert_eq!(result.month, 2);
         assert_eq!(result.day, 29);
 
         Ok(())
     }
+
+    #[test]
+    fn wrong_sequence_shows_remaining_format() -> TestResult {
+        let result = Interpreter::parse_datetime("2023-05/15 10:00", "%Y-%m-%d %H:%M");
+        let report = result.expect_err("`/` does not match the literal `-`");
+        let Some(InterpreterError::WrongSequence {
+            remaining_format, ..
+        }) = report.downcast_ref::<InterpreterError>()
+        else {
+            panic!("Expected a WrongSequence error, got {report:?}");
+        };
+        assert_eq!(remaining_format.as_deref(), Some("-%d %H:%M"));
+        let help = report.help().map(|help| help.to_string());
+        assert_eq!(help.as_deref(), Some("-%d %H:%M"));
+        Ok(())
+    }
+
+    #[test]
+    fn optional_groups() -> TestResult {
+        let format = "%Y-%m-%d[ %H:%M[:%S]]";
+        let date_only = Interpreter::parse_datetime("2024-03-09", format)?;
+        assert_eq!(
+            date_only,
+            Datetime {
+                year: 2024,
+                month: 3,
+                day: 9,
+                ..Default::default()
+            }
+        );
+
+        let without_seconds = Interpreter::parse_datetime("2024-03-09 17:45", format)?;
+        assert_eq!(
+            without_seconds,
+            Datetime {
+                hour: 17,
+                minute: 45,
+                ..date_only
+            }
+        );
+
+        let with_seconds = Interpreter::parse_datetime("2024-03-09 17:45:12", format)?;
+        assert_eq!(
+            with_seconds,
+            Datetime {
+                second: 12,
+                ..without_seconds
+            }
+        );
+
+        // A group that only partially matches is not silently dropped
+        assert!(Interpreter::parse_datetime("2024-03-09 17:4", format).is_err());
+        assert!(Interpreter::parse_datetime("2024-03-09 17:45:", format).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_input_is_rejected() -> TestResult {
+        let result = Interpreter::parse_datetime("2024-03-09 and more", "%Y-%m-%d");
+        let report = result.expect_err("Input is longer than the format");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::TrailingInput { trailing, .. }) if trailing == " and more"
+        ));
+        Ok(())
+    }
+
+    /// Small xorshift generator, so the test needs no extra dependencies
+    fn next_random(state: &mut u64) -> usize {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state as usize
+    }
+
+    #[test]
+    fn compiled_fast_path_matches_generic_path() -> TestResult {
+        let compiled = CompiledFormat::new("%Y%m%d%H%M%S")?;
+        assert!(compiled.fixed_width.is_some());
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..10_000 {
+            // Days up to 31 also produce invalid dates, which must fail alike
+            let input = format!(
+                "{:04}{:02}{:02}{:02}{:02}{:02}",
+                next_random(&mut state) % 10_000,
+                next_random(&mut state) % 12 + 1,
+                next_random(&mut state) % 31 + 1,
+                next_random(&mut state) % 24,
+                next_random(&mut state) % 60,
+                next_random(&mut state) % 60,
+            );
+            let generic = Interpreter::new(&input, &ParseOptions::default())
+                .parse_tokens(&compiled.tokens, &input, Parsed::default())
+                .and_then(|parsed| parsed.build(&input, &ParseOptions::default()))
+                .map(|(datetime, _)| datetime);
+            let fast = compiled.parse_fixed_width(&input);
+            assert_eq!(
+                fast,
+                generic.as_ref().ok().copied(),
+                "Failed on input: {input}"
+            );
+            assert_eq!(compiled.parse(&input).ok(), generic.ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn compiled_column() -> TestResult {
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        let inputs = (0..50_000)
+            .map(|_| {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    next_random(&mut state) % 10_000,
+                    next_random(&mut state) % 12 + 1,
+                    next_random(&mut state) % 28 + 1,
+                    next_random(&mut state) % 24,
+                    next_random(&mut state) % 60,
+                    next_random(&mut state) % 60,
+                )
+            })
+            .collect::<Vec<_>>();
+        let inputs = inputs.iter().map(String::as_str).collect::<Vec<_>>();
+        let format = "%Y-%m-%d %H:%M:%S";
+        let column = CompiledFormat::new(format)?.parse_column(&inputs);
+        assert_eq!(column.len(), inputs.len());
+        for (input, result) in inputs.iter().zip(column) {
+            assert_eq!(result?, Interpreter::parse_datetime(input, format)?);
+        }
+
+        let column =
+            CompiledFormat::new("%d/%m/%Y")?.parse_column(&["31/12/2024", "", "31/02/2024"]);
+        assert!(matches!(column.as_slice(), [Ok(_), Err(_), Err(_)]));
+        Ok(())
+    }
+
+    #[test]
+    fn width_overrides() -> TestResult {
+        let result = Interpreter::parse_datetime("012024-07-04", "%6Y-%m-%d")?;
+        assert_eq!((result.year, result.month, result.day), (12_024, 7, 4));
+        let result = Interpreter::parse_datetime("7/4/2024", "%1m/%1d/%Y")?;
+        assert_eq!((result.month, result.day), (7, 4));
+        // Mainframe style columns, with no separators at all
+        let result = Interpreter::parse_datetime("00202401310023", "%6Y%2m%2d%3H%1M")?;
+        assert_eq!(
+            (
+                result.year,
+                result.month,
+                result.day,
+                result.hour,
+                result.minute
+            ),
+            (2024, 1, 31, 2, 3)
+        );
+        assert!(Interpreter::parse_datetime("2024-07-04", "%6Y-%m-%d").is_err());
+        assert!(Interpreter::parse_datetime("7/14/2024", "%1m/%1d/%Y").is_err());
+
+        // The same widths take the fast path of compiled formats
+        let compiled = CompiledFormat::new("%6Y%2m%2d%3H%1M")?;
+        assert_eq!(compiled.fixed_width, Some(14));
+        assert_eq!(
+            compiled.parse_fixed_width("00202401310023"),
+            Some(Interpreter::parse_datetime(
+                "00202401310023",
+                "%6Y%2m%2d%3H%1M"
+            )?)
+        );
+        assert_eq!(CompiledFormat::new("%-2d%3f")?.fixed_width, None);
+        // Numbers too wide for a `usize` fall back to the generic path
+        let compiled = CompiledFormat::new("%30Y")?;
+        let nines = "9".repeat(30);
+        assert_eq!(compiled.parse_fixed_width(&nines), None);
+        assert!(compiled.parse(&nines).is_err());
+        assert!(Interpreter::parse_datetime(&nines, "%30Y").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn compiled_format_errors() -> TestResult {
+        let compiled = CompiledFormat::new("%y-%m-%d")?;
+        assert_eq!(
+            compiled.parse("24-02-29")?,
+            Interpreter::parse_datetime("24-02-29", "%y-%m-%d")?
+        );
+        assert!(compiled.parse("24-02-30").is_err());
+        assert!(compiled.parse("24-0a-01").is_err());
+        assert!(compiled.parse("24-02-011").is_err());
+
+        // Formats with names never take the fast path, but still parse
+        let compiled = CompiledFormat::new("%I:%M %p")?;
+        assert!(compiled.fixed_width.is_none());
+        assert_eq!(compiled.parse("03:15 PM")?.hour, 15);
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_percent_literal() -> TestResult {
+        let result = Interpreter::parse_datetime("%2024", r"\%%Y")?;
+        assert_eq!(result.year, 2024);
+        let result = Interpreter::parse_datetime("2024 at 100%", "%Y at 100%%")?;
+        assert_eq!(result.year, 2024);
+        Ok(())
+    }
+
+    #[test]
+    fn locale_meridiem() -> TestResult {
+        let options = ParseOptions {
+            locale: Locale {
+                am: "a.m.",
+                pm: "p.m.",
+                ..Locale::default()
+            },
+            ..ParseOptions::default()
+        };
+        let parse = |input| Interpreter::parse_datetime_with_options(input, "%I:%M %p", &options);
+        let result = parse("03:45 p.m.")?;
+        assert_eq!((result.hour, result.minute), (15, 45));
+        let result = parse("12:10 a.m.")?;
+        assert_eq!((result.hour, result.minute), (0, 10));
+
+        // The English markers are not accepted by the custom locale
+        assert!(parse("03:45 PM").is_err());
+        assert_eq!(
+            Interpreter::parse_datetime("03:45 PM", "%I:%M %p")?.hour,
+            15
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_number_targets() -> TestResult {
+        let (number, rest) = parse_number::<usize>("0042rest", 4)?;
+        assert_eq!((number, rest), (42, "rest"));
+
+        let (number, rest) = parse_number::<i32>("-0530", 3)?;
+        assert_eq!((number, rest), (-5, "30"));
+        let (number, _) = parse_number::<i32>("+12", 3)?;
+        assert_eq!(number, 12);
+
+        // Unsigned targets reject the sign, and short inputs are errors
+        assert!(parse_number::<usize>("-05", 3).is_err());
+        assert!(parse_number::<i32>("12", 3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn meridiem_after_seconds() -> TestResult {
+        // The literal space is its own token, consumed before `%p` runs
+        let spaced = Interpreter::parse_datetime("05:30:00 PM", "%I:%M:%S %p")?;
+        assert_eq!((spaced.hour, spaced.minute), (17, 30));
+
+        // Without a separator, `%p` reads straight after the seconds
+        let adjacent = Interpreter::parse_datetime("05:30:00PM", "%I:%M:%S%p")?;
+        assert_eq!(adjacent, spaced);
+        let compact = Interpreter::parse_datetime("053000PM", "%I%M%S%p")?;
+        assert_eq!(compact, spaced);
+        let noon = Interpreter::parse_datetime("120000PM", "%H%M%S%p")?;
+        assert_eq!(noon.hour, 12);
+        let midnight = Interpreter::parse_datetime("120000AM", "%H%M%S%p")?;
+        assert_eq!(midnight.hour, 0);
+
+        // A missing or doubled space is a mismatch, not silently skipped
+        assert!(Interpreter::parse_datetime("05:30:00PM", "%I:%M:%S %p").is_err());
+        assert!(Interpreter::parse_datetime("05:30:00 PM", "%I:%M:%S%p").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn several_options_at_once() -> TestResult {
+        let options = ParseOptions {
+            locale: Locale {
+                am: "am",
+                pm: "pm",
+                ..Locale::default()
+            },
+            century_pivot: 70,
+            case_insensitive: true,
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options(
+            "69-03-01t09:15 PM",
+            "%y-%m-%dT%I:%M %p",
+            &options,
+        )?;
+        assert_eq!(
+            result,
+            Datetime {
+                year: 2069,
+                month: 3,
+                day: 1,
+                hour: 21,
+                minute: 15,
+                ..Default::default()
+            }
+        );
+        let result = Interpreter::parse_datetime_with_options("70-03-01", "%y-%m-%d", &options)?;
+        assert_eq!(result.year, 1970);
+
+        // The defaults are strict about case and keep the 25 pivot
+        let defaults = ParseOptions::default();
+        assert!(
+            Interpreter::parse_datetime_with_options("03-01t09", "%m-%dT%H", &defaults).is_err()
+        );
+        assert_eq!(
+            Interpreter::parse_datetime_with_options("69", "%y", &defaults)?.year,
+            1969
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn compound_time_specifiers() -> TestResult {
+        let result = Interpreter::parse_datetime("14:30", "%R")?;
+        assert_eq!((result.hour, result.minute, result.second), (14, 30, 0));
+        let result = Interpreter::parse_datetime("2024-01-02 14:30:45", "%Y-%m-%d %T")?;
+        assert_eq!((result.hour, result.minute, result.second), (14, 30, 45));
+        assert!(Interpreter::parse_datetime("14:30", "%T").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn month_and_weekday_names() -> TestResult {
+        let result = Interpreter::parse_datetime("March 5, 2024", "%B %e, %Y")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 3, 5));
+        let result = Interpreter::parse_datetime("Tuesday 05 Mar 2024", "%A %d %b %Y")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 3, 5));
+        let report = Interpreter::parse_datetime("Friday 05 Mar 2024", "%A %d %b %Y")
+            .expect_err("2024-03-05 is a Tuesday");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::WeekdayMismatch { .. })
+        ));
+        let report = Interpreter::parse_datetime("Mars 2024", "%B %Y").expect_err("Not a month");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::WrongSequence { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn locale_date_time() -> TestResult {
+        let result = Interpreter::parse_datetime("Mon Jan  1 00:00:00 2024", "%c")?;
+        assert_eq!(result, Datetime::from_str("2024-01-01", "%Y-%m-%d")?);
+        let result = Interpreter::parse_datetime("Thu Feb 29 13:45:10 2024 UTC", "%c UTC")?;
+        assert_eq!((result.month, result.day, result.hour), (2, 29, 13));
+        let options = ParseOptions {
+            locale: Locale {
+                date_time_format: "%d/%m/%Y %c",
+                ..Locale::default()
+            },
+            ..ParseOptions::default()
+        };
+        assert!(Interpreter::parse_datetime_with_options("01/01/2024", "%c", &options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn hour_token() -> TestResult {
+        // Not lexed from any specifier, but reads like `%H`
+        let tokens = [
+            Token::Hour,
+            Token::Literal {
+                pattern: ":".to_string(),
+            },
+            Token::Minute,
+        ];
+        let options = ParseOptions::default();
+        let (result, _) = Interpreter::new("07:30", &options)
+            .parse_tokens(&tokens, "07:30", Parsed::default())?
+            .build("07:30", &options)?;
+        assert_eq!((result.hour, result.minute), (7, 30));
+        assert!(
+            Interpreter::new("7:30", &options)
+                .parse_tokens(&tokens, "7:30", Parsed::default())
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn utc_offsets() -> TestResult {
+        let offset = |input| {
+            Interpreter::parse_datetime(input, "%H:%M%z").map(|result| result.offset_minutes)
+        };
+        assert_eq!(offset("10:00+05:53")?, Some(353));
+        assert_eq!(offset("10:00+00:44")?, Some(44));
+        assert_eq!(offset("10:00-03:30")?, Some(-210));
+        assert_eq!(offset("10:00+0545")?, Some(345));
+        assert_eq!(offset("10:00+01")?, Some(60));
+        assert_eq!(offset("10:00+00:00")?, Some(0));
+        // An unknown local offset, not UTC
+        assert_eq!(offset("10:00-00:00")?, None);
+        assert_eq!(
+            Interpreter::parse_datetime("10:00", "%H:%M")?.offset_minutes,
+            None
+        );
+
+        assert_eq!(offset("10:00Z")?, Some(0));
+        assert_eq!(offset("10:00z")?, Some(0));
+        let result = Interpreter::parse_datetime("2024-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%S%z")?;
+        assert_eq!(result.offset_minutes, Some(0));
+        // A literal `Z` is only text
+        let result = Interpreter::parse_datetime("2024-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")?;
+        assert_eq!(result.offset_minutes, None);
+
+        assert!(offset("10:00+05:60").is_err());
+        assert!(offset("10:00+24:00").is_err());
+        assert!(offset("10:00 05:00").is_err());
+        assert!(offset("10:00+05:").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_month_names() -> TestResult {
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("Jan-2024", "%m-%Y", &lenient)?;
+        assert_eq!((result.year, result.month), (2024, 1));
+        let result = Interpreter::parse_datetime_with_options("September-2024", "%m-%Y", &lenient)?;
+        assert_eq!(result.month, 9);
+        let result = Interpreter::parse_datetime_with_options("03-2024", "%m-%Y", &lenient)?;
+        assert_eq!(result.month, 3);
+        assert!(Interpreter::parse_datetime_with_options("Foo-2024", "%m-%Y", &lenient).is_err());
+
+        assert!(Interpreter::parse_datetime("Jan-2024", "%m-%Y").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn time_zone_names() -> TestResult {
+        struct Stub;
+        impl TzResolver for Stub {
+            fn offset_for(&self, name: &str, _at: &Datetime) -> Option<i32> {
+                (name == "America/Sao_Paulo").then_some(-180)
+            }
+        }
+        let options = ParseOptions {
+            tz_resolver: Some(Arc::new(Stub)),
+            ..ParseOptions::default()
+        };
+        let parse =
+            |input| Interpreter::parse_datetime_with_options(input, "%Y-%m-%d %H:%M %Z", &options);
+        let result = parse("2024-05-17 10:00 America/Sao_Paulo")?;
+        assert_eq!((result.hour, result.offset_minutes), (10, Some(-180)));
+
+        let report = parse("2024-05-17 10:00 Europe/Lisbon").expect_err("Unknown to the stub");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::UnknownTimeZone { .. })
+        ));
+        assert!(parse("2024-05-17 10:00 ").is_err());
+        // Without a resolver
+        assert!(
+            Interpreter::parse_datetime("2024-05-17 America/Sao_Paulo", "%Y-%m-%d %Z").is_err()
+        );
+        // An explicit offset is kept
+        let result = Interpreter::parse_datetime_with_options(
+            "10:00-02:00 America/Sao_Paulo",
+            "%H:%M%z %Z",
+            &options,
+        )?;
+        assert_eq!(result.offset_minutes, Some(-120));
+        Ok(())
+    }
+
+    #[test]
+    fn time_zone_abbreviations() -> TestResult {
+        let parse = |input| Interpreter::parse_datetime(input, "%Y-%m-%d %H:%M %Z");
+        let summer = parse("2024-07-01 10:00 EDT")?;
+        let winter = parse("2024-01-01 10:00 EST")?;
+        assert_eq!(summer.offset_minutes, Some(-240));
+        assert_eq!(winter.offset_minutes, Some(-300));
+        let (summer, winter) = (
+            summer.zone_abbreviation.expect("Matched EDT"),
+            winter.zone_abbreviation.expect("Matched EST"),
+        );
+        assert_eq!((summer.name, summer.daylight_saving), ("EDT", true));
+        assert_eq!((winter.name, winter.daylight_saving), ("EST", false));
+        assert_eq!(
+            ZoneAbbreviation::find("CEST").map(|zone| zone.offset_minutes),
+            Some(120)
+        );
+        assert_eq!(ZoneAbbreviation::find("edt"), None);
+
+        // Lowercase only with case insensitive parsing, which keeps the
+        // canonical name of the entry rather than the input's spelling
+        assert!(parse("2024-07-01 10:00 edt").is_err());
+        let options = ParseOptions {
+            case_insensitive: true,
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("10:00 edt", "%H:%M %Z", &options)?;
+        assert_eq!(result.zone_abbreviation.map(|zone| zone.name), Some("EDT"));
+        assert_eq!(result.format("%H:%M %Z")?, "10:00 EDT");
+        // An explicit offset wins, but the name is still kept
+        let result = Interpreter::parse_datetime("10:00+01:00 EDT", "%H:%M%z %Z")?;
+        assert_eq!(result.offset_minutes, Some(60));
+        assert!(result.zone_abbreviation.is_some());
+        // Resolvers are asked first
+        struct Eastern;
+        impl TzResolver for Eastern {
+            fn offset_for(&self, name: &str, _at: &Datetime) -> Option<i32> {
+                match name {
+                    "EST" => Some(-299),
+                    "US/Eastern" => Some(-300),
+                    _ => None,
+                }
+            }
+        }
+        let options = ParseOptions {
+            tz_resolver: Some(Arc::new(Eastern)),
+            ..ParseOptions::default()
+        };
+        let parse = |input| Interpreter::parse_datetime_with_options(input, "%H:%M %Z", &options);
+        assert_eq!(parse("10:00 EST")?.offset_minutes, Some(-299));
+        assert_eq!(parse("10:00 EDT")?.offset_minutes, Some(-240));
+        assert!(parse("10:00 America/Sao_Paulo").is_err());
+        // Names only the resolver knows give no abbreviation
+        let result = parse("10:00 US/Eastern")?;
+        assert_eq!(result.offset_minutes, Some(-300));
+        assert_eq!(result.zone_abbreviation, None);
+        Ok(())
+    }
+
+    #[test]
+    fn daylight_saving_transitions() -> TestResult {
+        struct NewYork;
+        impl TzResolver for NewYork {
+            fn offset_for(&self, _name: &str, _at: &Datetime) -> Option<i32> {
+                Some(-300)
+            }
+            fn resolve(&self, _name: &str, at: &Datetime) -> Option<LocalResult> {
+                Some(match (at.month, at.day, at.hour) {
+                    (11, 3, 1) => LocalResult::Ambiguous {
+                        earliest: -240,
+                        latest: -300,
+                    },
+                    (3, 10, 2) => LocalResult::Gap,
+                    _ => LocalResult::Unique(-300),
+                })
+            }
+        }
+        let options = ParseOptions {
+            tz_resolver: Some(Arc::new(NewYork)),
+            ..ParseOptions::default()
+        };
+        let parse = |input| {
+            Interpreter::parse_datetime_with_options(input, "%Y-%m-%d %H:%M[%z] %Z", &options)
+        };
+        assert_eq!(
+            parse("2024-01-01 01:30 America/New_York")?.offset_minutes,
+            Some(-300)
+        );
+        let report = parse("2024-11-03 01:30 America/New_York").expect_err("Ambiguous");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::AmbiguousLocalTime {
+                earliest: -240,
+                latest: -300,
+                ..
+            })
+        ));
+        let report = parse("2024-03-10 02:30 America/New_York").expect_err("Skipped");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::NonexistentLocalTime { .. })
+        ));
+        // An explicit offset settles which of the repeated times is meant
+        let result = parse("2024-11-03 01:30-04:00 America/New_York")?;
+        assert_eq!(result.offset_minutes, Some(-240));
+        assert!(result.is_dst_ambiguous("America/New_York", &NewYork));
+        assert!(!Datetime::from_ymd(2024, 1, 1)?.is_dst_ambiguous("America/New_York", &NewYork));
+        Ok(())
+    }
+
+    #[test]
+    fn iso_week_numbers() -> TestResult {
+        let result = Interpreter::parse_datetime("2025-W01", "%G-W%V")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 12, 30));
+        let result = Interpreter::parse_datetime("2024-W07 Thursday", "%Y-W%V %A")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 2, 15));
+        assert!(Interpreter::parse_datetime("2024-W54", "%G-W%V").is_err());
+
+        let report = Interpreter::parse_datetime("07", "%V").expect_err("No year");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::WeekWithoutYear {
+                specifier: "%V",
+                ..
+            })
+        ));
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            reference_year: Some(2024),
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("07", "%V", &lenient)?;
+        assert_eq!((result.year, result.month, result.day), (2024, 2, 12));
+        let strict = ParseOptions {
+            reference_year: Some(2026),
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("W01-1", "W%V-%u", &strict)?;
+        assert_eq!((result.year, result.month, result.day), (2025, 12, 29));
+        let result = Interpreter::parse_datetime_with_options("W01-5", "W%V-%u", &strict)?;
+        assert_eq!((result.year, result.month, result.day), (2026, 1, 2));
+        let result = Interpreter::parse_datetime_with_options("W53-7", "W%V-%u", &strict)?;
+        assert_eq!((result.year, result.month, result.day), (2027, 1, 3));
+        assert!(Interpreter::parse_datetime_with_options("W53-1", "W%V-%u", &lenient).is_err());
+        // A year in the input wins over the reference
+        let result = Interpreter::parse_datetime_with_options("2024-W01-1", "%G-W%V-%u", &strict)?;
+        assert_eq!((result.year, result.month, result.day), (2024, 1, 1));
+
+        // Weeks from the first Monday or Sunday start on that day
+        let result = Interpreter::parse_datetime("2024-10", "%Y-%W")?;
+        assert_eq!((result.month, result.day), (3, 4));
+        let result = Interpreter::parse_datetime("2024-10", "%Y-%U")?;
+        assert_eq!((result.month, result.day), (3, 10));
+        let result = Interpreter::parse_datetime("2024-00 Sat", "%Y-%U %a")?;
+        assert_eq!((result.month, result.day), (1, 6));
+        assert!(Interpreter::parse_datetime("2024-00 Sun", "%Y-%U %a").is_err());
+        // The error names the week number that was read
+        for specifier in ["%U", "%W"] {
+            let report = Interpreter::parse_datetime("10", specifier).expect_err("No year");
+            let Some(InterpreterError::WeekWithoutYear {
+                specifier: named,
+                help,
+                ..
+            }) = report.downcast_ref::<InterpreterError>()
+            else {
+                panic!("Failed on {specifier}: {report:?}");
+            };
+            assert_eq!(*named, specifier);
+            assert!(!help.contains("%G"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_inputs_do_not_panic() {
+        const PIECES: &[&str] = &[
+            "0",
+            "1",
+            "9",
+            "12",
+            "2024",
+            "-",
+            "+",
+            ":",
+            "/",
+            " ",
+            "T",
+            "Z",
+            "é",
+            "日",
+            "\u{200B}",
+            "\u{0}",
+            "\n",
+            "\u{7F}",
+            "PM",
+            "Jan",
+            "Mon",
+            "\u{1F600}",
+            ".",
+        ];
+        const FORMATS: &[&str] = &[
+            "%Y-%m-%d",
+            "%Y%m%d%H%M%S",
+            "%d/%m/%y %I:%M %p",
+            "%Y-%m-%dT%H:%M:%S[.%f]%z",
+            "%a %b %e %T %Y",
+            "%G-W%V",
+            "%c",
+        ];
+        let mut state = 0x2545_F491_4F6C_DD1D;
+        for _ in 0..5_000 {
+            let length = next_random(&mut state) % 12;
+            let input: String = (0..length)
+                .map(|_| PIECES[next_random(&mut state) % PIECES.len()])
+                .collect();
+            for format in FORMATS {
+                let _ = Interpreter::parse_datetime(&input, format);
+                let _ = CompiledFormat::new(format).map(|compiled| compiled.parse(&input));
+            }
+        }
+    }
+
+    #[test]
+    fn padding_flags() -> TestResult {
+        let result = Interpreter::parse_datetime("3/5/2024 7:08", "%-m/%-d/%Y %-H:%M")?;
+        assert_eq!((result.month, result.day, result.hour), (3, 5, 7));
+        let result = Interpreter::parse_datetime("12/25/2024", "%-m/%-d/%Y")?;
+        assert_eq!((result.month, result.day), (12, 25));
+        let result = Interpreter::parse_datetime("Mar  5", "%b %_d")?;
+        assert_eq!((result.month, result.day), (3, 5));
+        assert!(Interpreter::parse_datetime("/5/2024", "%-m/%-d/%Y").is_err());
+        assert!(Interpreter::parse_datetime("3/5", "%m/%d").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn widths() -> TestResult {
+        let result = Interpreter::parse_datetime("0005|  3|7|123", "%4d|%_3m|%-4H|%3f")?;
+        assert_eq!(
+            (result.day, result.month, result.hour, result.nanosecond),
+            (5, 3, 7, 123_000_000)
+        );
+        let result = Interpreter::parse_datetime("12:30:15.250777", "%T.%3f777")?;
+        assert_eq!((result.second, result.nanosecond), (15, 250_000_000));
+        let result = Interpreter::parse_datetime("2024 5 7", "%4Y %1m %_3d")?;
+        assert_eq!((result.month, result.day), (5, 7));
+        for (input, format) in [
+            ("005", "%4d"),
+            ("10:1", "%H:%2f"),
+            ("12345", "%-4d"),
+            ("5", "%02d"),
+        ] {
+            assert!(
+                Interpreter::parse_datetime(input, format).is_err(),
+                "Failed on input: {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fraction_without_separator_token() -> TestResult {
+        let result = Interpreter::parse_datetime("120000.5", "%H%M%S%f")?;
+        assert_eq!(
+            (result.hour, result.second, result.nanosecond),
+            (12, 0, 500_000_000)
+        );
+        let result = Interpreter::parse_datetime("120000,25", "%H%M%S%f")?;
+        assert_eq!(result.nanosecond, 250_000_000);
+        let result = Interpreter::parse_datetime("120000", "%H%M%S%f")?;
+        assert_eq!((result.hour, result.nanosecond), (12, 0));
+        let result = Interpreter::parse_datetime("12000075", "%H%M%S%f")?;
+        assert_eq!(result.nanosecond, 750_000_000);
+        let result = Interpreter::parse_datetime("12:00:00Z", "%H:%M:%S%fZ")?;
+        assert_eq!(result.nanosecond, 0);
+
+        // Its own separator still needs digits after it
+        assert!(Interpreter::parse_datetime("120000.", "%H%M%S%f").is_err());
+        // The separator is not doubled after one in the format
+        assert!(Interpreter::parse_datetime("12:00:00..5", "%H:%M:%S.%f").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dangling_decimal_point() -> TestResult {
+        for format in ["%H:%M:%S.%f", "%H:%M:%S[.%f]"] {
+            let result = Interpreter::parse_datetime("12:00:00.0", format)?;
+            assert_eq!((result.second, result.nanosecond), (0, 0));
+            let result = Interpreter::parse_datetime("12:00:00.000001", format)?;
+            assert_eq!(result.nanosecond, 1_000);
+            assert!(
+                Interpreter::parse_datetime("12:00:00.", format).is_err(),
+                "Failed on format: {format}"
+            );
+        }
+        // Without the separator the fraction is simply absent
+        let result = Interpreter::parse_datetime("12:00:00", "%H:%M:%S[.%f]")?;
+        assert_eq!(result.nanosecond, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn alternation() -> TestResult {
+        for input in ["2024-05", "2024/05"] {
+            let result = Interpreter::parse_datetime(input, "%Y(-|/)%m")?;
+            assert_eq!(
+                (result.year, result.month),
+                (2024, 5),
+                "Failed on input: {input}"
+            );
+        }
+        assert!(Interpreter::parse_datetime("2024.05", "%Y(-|/)%m").is_err());
+
+        // Alternatives may hold specifiers and backtrack into the rest
+        let format = "(%d %B|%B %d), %Y";
+        for input in ["17 May, 2024", "May 17, 2024"] {
+            let result = Interpreter::parse_datetime(input, format)?;
+            assert_eq!(
+                (result.month, result.day),
+                (5, 17),
+                "Failed on input: {input}"
+            );
+        }
+        let result = Interpreter::parse_datetime("10:30 h", "%H:%M( h|h|)")?;
+        assert_eq!(result.minute, 30);
+        let result = Interpreter::parse_datetime("10:30", "%H:%M( h|h|)")?;
+        assert_eq!(result.minute, 30);
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_tolerance() -> TestResult {
+        let tolerant = ParseOptions {
+            trailing_tolerant: true,
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("12:00", "%H:%M:%S", &tolerant)?;
+        assert_eq!((result.hour, result.minute, result.second), (12, 0, 0));
+        let result = Interpreter::parse_datetime_with_options("12", "%H:%M:%S", &tolerant)?;
+        assert_eq!((result.hour, result.minute), (12, 0));
+        assert!(Interpreter::parse_datetime("12:00", "%H:%M:%S").is_err());
+
+        // The input must still stop right before a separator
+        for input in ["", "12:", "12:00:"] {
+            assert!(
+                Interpreter::parse_datetime_with_options(input, "%H:%M:%S", &tolerant).is_err(),
+                "Failed on input: {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn custom_specifiers() -> TestResult {
+        let options = ParseOptions::default().with_custom('x', |input: &str| {
+            let quarter = input
+                .strip_prefix('Q')
+                .and_then(|rest| rest.split_at_checked(1))
+                .filter(|(digit, _)| ("1"..="4").contains(digit));
+            match quarter {
+                Some((digit, rest)) => {
+                    let quarter: usize = digit.parse().into_diagnostic()?;
+                    Ok((FieldUpdate::Month(quarter * 3 - 2), rest))
+                }
+                None => Err(miette::miette!("Expected a quarter like `Q3`")),
+            }
+        });
+        let result = Interpreter::parse_datetime_with_options("2024-Q3", "%Y-%x", &options)?;
+        assert_eq!((result.year, result.month, result.day), (2024, 7, 1));
+        assert!(Interpreter::parse_datetime_with_options("2024-Q5", "%Y-%x", &options).is_err());
+        assert!(Interpreter::parse_datetime_with_options("2024-Q3!", "%Y-%x", &options).is_err());
+        // Parsers must hand back what follows their part of the input
+        let longer = ParseOptions::default().with_custom('x', |_: &str| {
+            Ok((FieldUpdate::Nothing, "a longer string than given"))
+        });
+        let unrelated = ParseOptions::default().with_custom('x', |input: &str| {
+            Ok((FieldUpdate::Nothing, &"ab"[..input.len().min(1)]))
+        });
+        for options in [&longer, &unrelated] {
+            let report = Interpreter::parse_datetime_with_options("2024-Q3", "%Y-%x", options)
+                .expect_err("Not a suffix");
+            assert!(matches!(
+                report.downcast_ref::<InterpreterError>(),
+                Some(InterpreterError::CustomRest { specifier: 'x', .. })
+            ));
+        }
+        let everything =
+            ParseOptions::default().with_custom('x', |_: &str| Ok((FieldUpdate::Nothing, "")));
+        assert!(Interpreter::parse_datetime_with_options("2024-Q3", "%Y-%x", &everything).is_ok());
+        // Without the registration `%x` is an invalid specifier
+        let report = Interpreter::parse_datetime("2024-Q3", "%Y-%x").expect_err("Unknown %x");
+        assert!(matches!(
+            report.downcast_ref::<LexerError>(),
+            Some(LexerError::InvalidFormat { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn trimmed_input() -> TestResult {
+        let trim = ParseOptions {
+            trim_input: true,
+            ..ParseOptions::default()
+        };
+        for input in [
+            "\u{FEFF}2024-01-01",
+            "  2024-01-01  ",
+            "\u{FEFF} 2024-01-01\n",
+        ] {
+            let result = Interpreter::parse_datetime_with_options(input, "%Y-%m-%d", &trim)?;
+            assert_eq!(
+                (result.year, result.month, result.day),
+                (2024, 1, 1),
+                "Failed on input: {input:?}"
+            );
+            assert!(
+                Interpreter::parse_datetime(input, "%Y-%m-%d").is_err(),
+                "Failed on input: {input:?}"
+            );
+        }
+        // Only the ends are trimmed
+        assert!(
+            Interpreter::parse_datetime_with_options("2024- 01-01", "%Y-%m-%d", &trim).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn input_length_limit() -> TestResult {
+        let limited = ParseOptions {
+            max_input_len: Some(10),
+            ..ParseOptions::default()
+        };
+        let result = Interpreter::parse_datetime_with_options("2024-01-01", "%Y-%m-%d", &limited)?;
+        assert_eq!(result.year, 2024);
+        let input = format!("2024-01-01{}", " ".repeat(10_000));
+        let report = Interpreter::parse_datetime_with_options(&input, "%Y-%m-%d", &limited)
+            .expect_err("Input too long");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::InputTooLong {
+                length: 10_010,
+                limit: 10
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_separators() -> TestResult {
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        for input in ["2024--01--01", "2024-01---01", "2024-01-01"] {
+            let result = Interpreter::parse_datetime_with_options(input, "%Y-%m-%d", &lenient)?;
+            assert_eq!(
+                (result.year, result.month, result.day),
+                (2024, 1, 1),
+                "Failed on input: {input}"
+            );
+        }
+        assert!(Interpreter::parse_datetime("2024--01--01", "%Y-%m-%d").is_err());
+        // Only single character literals repeat
+        assert!(
+            Interpreter::parse_datetime_with_options("2024 - - 01", "%Y - %m", &lenient).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_numbers_and_whitespace() -> TestResult {
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        let parse =
+            |input, format| Interpreter::parse_datetime_with_options(input, format, &lenient);
+        let result = parse("1/2/24", "%d/%m/%y")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 2, 1));
+        let result = parse("2024-01-01  \u{A0} 9:05", "%Y-%m-%d %H:%M")?;
+        assert_eq!((result.day, result.hour, result.minute), (1, 9, 5));
+        // Days past the end of the month are moved back to its last day
+        let result = parse("2023-02-31", "%Y-%m-%d")?;
+        assert_eq!((result.month, result.day), (2, 28));
+
+        for input in ["1/2/24", "2023-02-31"] {
+            assert!(Interpreter::parse_datetime(input, "%d/%m/%y").is_err());
+        }
+        assert!(parse("24-01-01", "%Y-%m-%d").is_err());
+        // Months past December are moved back to it, but nothing comes
+        // before January
+        assert_eq!(parse("2024-13-31", "%Y-%m-%d")?.month, 12);
+        assert!(parse("2024-00-01", "%Y-%m-%d").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_warnings() -> TestResult {
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        let parse = |input: &str, format: &str| {
+            Interpreter::parse_datetime_with_warnings(input, format, &lenient)
+        };
+        let (result, warnings) = parse("2024-13-01", "%Y-%m-%d")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 12, 1));
+        assert_eq!(warnings, vec![Warning::MonthClamped { got: 13 }]);
+
+        let (result, warnings) = parse("Mond  2023--2-31 9:05", "%a %Y-%m-%d %H:%M")?;
+        assert_eq!((result.month, result.day, result.hour), (2, 28, 9));
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::WeekdayPrefix {
+                    prefix: "Mond".to_string(),
+                    weekday: "Monday".to_string(),
+                },
+                Warning::Whitespace {
+                    got: "  ".to_string(),
+                },
+                Warning::RepeatedSeparator {
+                    separator: "-".to_string(),
+                },
+                Warning::Unpadded {
+                    field: "Month".to_string(),
+                    got: "2".to_string(),
+                },
+                Warning::Unpadded {
+                    field: "Hour".to_string(),
+                    got: "9".to_string(),
+                },
+                Warning::DayClamped { got: 31, last: 28 },
+                Warning::WeekdayMismatch {
+                    named: "Monday".to_string(),
+                    actual: "Tuesday".to_string(),
+                },
+            ]
+        );
+        let (_, warnings) = parse("2024-Feb-01", "%Y-%m-%d")?;
+        assert_eq!(
+            warnings,
+            vec![Warning::MonthName {
+                name: "Feb".to_string(),
+            }]
+        );
+        // Alternatives that were tried and abandoned leave no warnings
+        let (_, warnings) = parse("2024-2-01", "%Y-(%m-%d.|%m-%d)")?;
+        assert_eq!(warnings.len(), 1);
+
+        // Inputs that follow the format, or strict parsing, give none
+        let (_, warnings) = parse("Tue 2024-02-20 09:05", "%a %Y-%m-%d %H:%M")?;
+        assert!(warnings.is_empty());
+        let (_, warnings) = Interpreter::parse_datetime_with_warnings(
+            "2024-02-20",
+            "%Y-%m-%d",
+            &ParseOptions::default(),
+        )?;
+        assert!(warnings.is_empty());
+        assert!(
+            Interpreter::parse_datetime_with_warnings(
+                "2024-13-01",
+                "%Y-%m-%d",
+                &ParseOptions::default()
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn redundant_weekday_prefix() -> TestResult {
+        let result = Interpreter::parse_datetime("Mon 2024-01-01", "%a %Y-%m-%d")?;
+        assert_eq!((result.year, result.month, result.day), (2024, 1, 1));
+        let report =
+            Interpreter::parse_datetime("Tue 2024-01-01", "%a %Y-%m-%d").expect_err("Monday");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::WeekdayMismatch { .. })
+        ));
+        let lenient = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        let result =
+            Interpreter::parse_datetime_with_options("Tue 2024-01-01", "%a %Y-%m-%d", &lenient)?;
+        assert_eq!((result.year, result.month, result.day), (2024, 1, 1));
+        // The name must still be a weekday
+        assert!(
+            Interpreter::parse_datetime_with_options("Foo 2024-01-01", "%a %Y-%m-%d", &lenient)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_weekday_prefixes() -> TestResult {
+        let options = ParseOptions {
+            strictness: Strictness::Lenient,
+            ..ParseOptions::default()
+        };
+        let parse = |input: &str, format: &str| {
+            Interpreter::parse_datetime_with_options(input, format, &options)
+        };
+        for input in ["M", "Mo", "Mon", "Mond", "Monda", "Monday"] {
+            for format in ["%a %Y-%m-%d", "%A %Y-%m-%d"] {
+                let result = parse(&format!("{input} 2024-01-01"), format)?;
+                assert_eq!(result.day, 1, "Failed on input: {input}");
+            }
+        }
+        // Other weekdays, and one followed by punctuation
+        assert_eq!(parse("Tu 2024-01-02", "%a %Y-%m-%d")?.day, 2);
+        assert_eq!(parse("Thur, 2024-01-04", "%a, %Y-%m-%d")?.day, 4);
+
+        let report = parse("S 2024-01-06", "%a %Y-%m-%d").expect_err("Saturday or Sunday");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::AmbiguousName { .. })
+        ));
+        assert!(parse("T 2024-01-02", "%A %Y-%m-%d").is_err());
+        assert!(parse("Mondays 2024-01-01", "%A %Y-%m-%d").is_err());
+        assert!(parse("2024-01-01", "%A%Y-%m-%d").is_err());
+
+        // Strict parsing keeps exact names
+        assert!(Interpreter::parse_datetime("Mond 2024-01-01", "%a %Y-%m-%d").is_err());
+        assert!(Interpreter::parse_datetime("Mon 2024-01-01", "%A %Y-%m-%d").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn weekday_numbers() -> TestResult {
+        // 2024-01-01 is a Monday and 2024-01-07 a Sunday
+        let result = Interpreter::parse_datetime("1 2024-01-01", "%u %Y-%m-%d")?;
+        assert_eq!(result.day, 1);
+        let result = Interpreter::parse_datetime("7 2024-01-07", "%u %Y-%m-%d")?;
+        assert_eq!(result.day, 7);
+        let result = Interpreter::parse_datetime("1 2024-01-01", "%w %Y-%m-%d")?;
+        assert_eq!(result.day, 1);
+        let result = Interpreter::parse_datetime("0 2024-01-07", "%w %Y-%m-%d")?;
+        assert_eq!(result.day, 7);
+
+        let report =
+            Interpreter::parse_datetime("2 2024-01-01", "%u %Y-%m-%d").expect_err("Monday is 1");
+        assert!(matches!(
+            report.downcast_ref::<InterpreterError>(),
+            Some(InterpreterError::WeekdayMismatch { .. })
+        ));
+        assert!(Interpreter::parse_datetime("7 2024-01-07", "%w %Y-%m-%d").is_err());
+        assert!(Interpreter::parse_datetime("0 2024-01-07", "%u %Y-%m-%d").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unix_timestamps() -> TestResult {
+        let result = Interpreter::parse_datetime("1700000000", "%s")?;
+        assert_eq!(result, Datetime::from_unix_timestamp(1_700_000_000)?);
+        let result = Interpreter::parse_datetime("1700000000.5", "%s")?;
+        assert_eq!(result.to_unix_timestamp(), 1_700_000_000);
+        assert_eq!(result.nanosecond, 500_000_000);
+        let result = Interpreter::parse_datetime("1700000000.123456789123", "%s")?;
+        assert_eq!(result.nanosecond, 123_456_789);
+        let result = Interpreter::parse_datetime("-1.25", "%s")?;
+        assert_eq!(
+            (result.to_unix_timestamp(), result.nanosecond),
+            (-2, 750_000_000)
+        );
+        let result = Interpreter::parse_datetime("@0 +0100", "@%s %z")?;
+        assert_eq!((result.year, result.offset_minutes), (1970, Some(60)));
+
+        for input in [
+            "",
+            "-",
+            "1700000000.",
+            "99999999999999999999",
+            "-99999999999",
+        ] {
+            assert!(
+                Interpreter::parse_datetime(input, "%s").is_err(),
+                "Failed on input: {input}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn roman_months() -> TestResult {
+        let roman = ParseOptions {
+            roman_months: true,
+            ..ParseOptions::default()
+        };
+        for (input, month) in [
+            ("I-2024", 1),
+            ("IV-2024", 4),
+            ("IX-2024", 9),
+            ("XII-2024", 12),
+        ] {
+            let result = Interpreter::parse_datetime_with_options(input, "%m-%Y", &roman)?;
+            assert_eq!(
+                (result.year, result.month),
+                (2024, month),
+                "Failed on {input}"
+            );
+        }
+        // Numbers keep working
+        let result = Interpreter::parse_datetime_with_options("04-2024", "%m-%Y", &roman)?;
+        assert_eq!(result.month, 4);
+        assert!(Interpreter::parse_datetime_with_options("XIII-2024", "%m-%Y", &roman).is_err());
+        assert!(Interpreter::parse_datetime_with_options("iv-2024", "%m-%Y", &roman).is_err());
+        assert!(Interpreter::parse_datetime("IV-2024", "%m-%Y").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,59 @@
+use miette::Error;
+
+use crate::datetime::Datetime;
+
+/// Days at the end of which a leap second was inserted, as UTC's
+/// `23:59:60`. The crate bundles no list, which would go stale with every
+/// announcement, so callers that need exact intervals supply their own,
+/// e.g.: from the IERS bulletins. The default table is empty, treating UTC
+/// as if it never had leap seconds.
+/// # Examples
+/// ```
+/// use datetime::{datetime::Datetime, leap_seconds::LeapSecondTable};
+/// let table = LeapSecondTable::new([
+///     Datetime::from_ymd(2015, 6, 30).unwrap(),
+///     Datetime::from_ymd(2016, 12, 31).unwrap(),
+/// ])
+/// .unwrap();
+/// assert_eq!(table.len(), 2);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LeapSecondTable {
+    /// Unix timestamps of the midnights right after each leap second, in
+    /// order
+    insertions: Vec<i64>,
+}
+
+impl LeapSecondTable {
+    /// A table of the leap seconds ending each of `days`, in any order.
+    /// Only the date of each is used, and repeated days count once. Returns
+    /// an error for an invalid date, or a day ending past [`Datetime::MAX`].
+    pub fn new(days: impl IntoIterator<Item = Datetime>) -> Result<Self, Error> {
+        let mut insertions = days
+            .into_iter()
+            .map(|day| {
+                Datetime::from_ymd(day.year, day.month, day.day)?
+                    .add_days(1)?
+                    .checked_timestamp()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        insertions.sort_unstable();
+        insertions.dedup();
+        Ok(Self { insertions })
+    }
+    pub fn len(&self) -> usize {
+        self.insertions.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.insertions.is_empty()
+    }
+    /// Leap seconds inserted after the Unix timestamp `start` and no later
+    /// than `end`, that is, whose midnight falls in `start + 1..=end`
+    pub(crate) fn count_between(&self, start: i64, end: i64) -> i64 {
+        let count = |timestamp| {
+            self.insertions
+                .partition_point(|insertion| *insertion <= timestamp)
+        };
+        count(end).saturating_sub(count(start)) as i64
+    }
+}
@@ -3,42 +3,277 @@ use core::fmt;
 use miette::{Diagnostic, SourceSpan};
 use thiserror::Error;
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub(crate) enum Token {
     FullYear,
     HalfYear,
+    /// `%G`, the ISO 8601 week-numbering year
+    IsoYear,
+    /// `%V`, the ISO 8601 week of the year
+    IsoWeek,
+    /// `%W`, the week of the year from the first Monday
+    MondayWeek,
+    /// `%U`, the week of the year from the first Sunday
+    SundayWeek,
     FullMonth,
     WrittenMonth,
+    /// `%b`, an abbreviated month name
+    ShortMonth,
     Day,
+    /// `%j`, the day of the year from 001
+    DayOfYear,
+    /// `%A`, a weekday name
+    FullWeekday,
+    /// `%a`, an abbreviated weekday name
+    ShortWeekday,
+    /// `%u`, the weekday as a number from 1 for Monday to 7 for Sunday
+    IsoWeekdayNumber,
+    /// `%w`, the weekday as a number from 0 for Sunday to 6 for Saturday
+    WeekdayNumber,
     TwentyFourHourDay,
     TwelveHourDay,
     Hour,
     Minute,
     Second,
-    Literal { pattern: String },
+    /// `%f`, the fraction of a second after the decimal point. Right after
+    /// another field, as in `%S%f`, it reads the `.` or `,` too, and may be
+    /// missing from the input.
+    Fraction,
+    /// `%s`, seconds since the Unix epoch, with an optional fraction
+    Epoch,
+    Literal {
+        pattern: String,
+    },
     AmOrPm,
+    /// `%z`, an offset from UTC like `+05:30`
+    UtcOffset,
+    /// `%Z`, a time zone name like `America/Sao_Paulo` or an abbreviation
+    /// like `EDT`
+    TimeZoneName,
+    /// `%c`, the locale's own date and time format
+    LocaleDateTime,
+    /// A number with a padding flag or a width, like `%-d` or `%4H`. `%e`
+    /// is `%_d`. `%f` takes a width too, as in `%3f`, for that many digits.
+    Padded {
+        padding: Padding,
+        width: Option<usize>,
+        token: Box<Token>,
+    },
+    /// `[`, opens a group that may be missing from the input
+    OptionalStart,
+    /// `]`, closes the innermost optional group
+    OptionalEnd,
+    /// `(`, opens a group of alternatives, of which one must match
+    AlternationStart,
+    /// `|`, separates the alternatives of the innermost `(` group
+    Alternative,
+    /// `)`, closes the innermost group of alternatives
+    AlternationEnd,
+    /// A specifier registered with [`ParseOptions::with_custom`]
+    ///
+    /// [`ParseOptions::with_custom`]: crate::options::ParseOptions::with_custom
+    Custom(char),
 }
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::FullYear | Self::HalfYear => write!(f, "Year"),
-            Self::FullMonth | Self::WrittenMonth => write!(f, "Month"),
+            Self::IsoYear => write!(f, "ISO year"),
+            Self::IsoWeek => write!(f, "ISO week"),
+            Self::MondayWeek | Self::SundayWeek => write!(f, "Week"),
+            Self::FullMonth | Self::WrittenMonth | Self::ShortMonth => write!(f, "Month"),
             Self::Day => write!(f, "Day"),
+            Self::DayOfYear => write!(f, "Day of the year"),
+            Self::FullWeekday
+            | Self::ShortWeekday
+            | Self::IsoWeekdayNumber
+            | Self::WeekdayNumber => write!(f, "Weekday"),
             Self::TwentyFourHourDay | Self::TwelveHourDay | Self::Hour => write!(f, "Hour"),
             Self::Minute => write!(f, "Minute"),
             Self::Second => write!(f, "Second"),
+            Self::Fraction => write!(f, "Fraction of a second"),
+            Self::Epoch => write!(f, "Unix timestamp"),
             Self::Literal { pattern: _ } => write!(f, "Literal"),
             Self::AmOrPm => write!(f, "Am or Pm"),
+            Self::UtcOffset => write!(f, "UTC offset"),
+            Self::TimeZoneName => write!(f, "Time zone"),
+            Self::LocaleDateTime => write!(f, "Date and time"),
+            Self::Padded { token, .. } => token.fmt(f),
+            Self::OptionalStart | Self::OptionalEnd => write!(f, "Optional group"),
+            Self::AlternationStart | Self::Alternative | Self::AlternationEnd => {
+                write!(f, "Alternatives")
+            }
+            Self::Custom(specifier) => write!(f, "Custom `%{specifier}`"),
+        }
+    }
+}
+
+impl Token {
+    /// The format specifier that lexes into this token
+    pub(crate) fn as_format(&self) -> String {
+        match self {
+            Self::FullYear => "%Y".to_string(),
+            Self::HalfYear => "%y".to_string(),
+            Self::IsoYear => "%G".to_string(),
+            Self::IsoWeek => "%V".to_string(),
+            Self::MondayWeek => "%W".to_string(),
+            Self::SundayWeek => "%U".to_string(),
+            Self::FullMonth => "%m".to_string(),
+            Self::WrittenMonth => "%B".to_string(),
+            Self::ShortMonth => "%b".to_string(),
+            Self::Day => "%d".to_string(),
+            Self::DayOfYear => "%j".to_string(),
+            Self::FullWeekday => "%A".to_string(),
+            Self::ShortWeekday => "%a".to_string(),
+            Self::IsoWeekdayNumber => "%u".to_string(),
+            Self::WeekdayNumber => "%w".to_string(),
+            Self::TwentyFourHourDay | Self::Hour => "%H".to_string(),
+            Self::TwelveHourDay => "%I".to_string(),
+            Self::Minute => "%M".to_string(),
+            Self::Second => "%S".to_string(),
+            Self::Fraction => "%f".to_string(),
+            Self::Epoch => "%s".to_string(),
+            Self::Literal { pattern } => pattern
+                .chars()
+                .map(|c| match c {
+                    '%' => "%%".to_string(),
+                    '[' | ']' | '(' | ')' | '|' => format!("\\{c}"),
+                    c => c.to_string(),
+                })
+                .collect(),
+            Self::AmOrPm => "%p".to_string(),
+            Self::UtcOffset => "%z".to_string(),
+            Self::TimeZoneName => "%Z".to_string(),
+            Self::LocaleDateTime => "%c".to_string(),
+            Self::Padded {
+                padding,
+                width,
+                token,
+            } => {
+                let flag = match padding {
+                    Padding::Zero => '0',
+                    Padding::Space => '_',
+                    Padding::Unpadded => '-',
+                };
+                let width = width.map(|width| width.to_string()).unwrap_or_default();
+                token
+                    .as_format()
+                    .replacen('%', &format!("%{flag}{width}"), 1)
+            }
+            Self::OptionalStart => "[".to_string(),
+            Self::OptionalEnd => "]".to_string(),
+            Self::AlternationStart => "(".to_string(),
+            Self::Alternative => "|".to_string(),
+            Self::AlternationEnd => ")".to_string(),
+            Self::Custom(specifier) => format!("%{specifier}"),
+        }
+    }
+}
+
+/// How numbers narrower than their field are filled, chosen with the
+/// GNU `date` flags after the `%`
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub(crate) enum Padding {
+    /// `0`, the default, e.g.: `05`
+    Zero,
+    /// `_`, e.g.: ` 5`
+    Space,
+    /// `-`, e.g.: `5`
+    Unpadded,
+}
+
+impl Token {
+    /// Digits in the number matched by this token when fully padded, or
+    /// `None` if it is not a number
+    pub(crate) fn numeric_width(&self) -> Option<usize> {
+        match self {
+            Self::FullYear | Self::IsoYear => Some(4),
+            Self::DayOfYear => Some(3),
+            Self::IsoWeekdayNumber | Self::WeekdayNumber => Some(1),
+            Self::HalfYear
+            | Self::IsoWeek
+            | Self::MondayWeek
+            | Self::SundayWeek
+            | Self::FullMonth
+            | Self::Day
+            | Self::TwentyFourHourDay
+            | Self::TwelveHourDay
+            | Self::Hour
+            | Self::Minute
+            | Self::Second => Some(2),
+            Self::Padded { width, token, .. } => width.or_else(|| token.numeric_width()),
+            _ => None,
         }
     }
 }
 
+/// Tokens of the specifiers that are numbers, which may take a padding flag
+/// and a width
+fn numeric_specifier(ident: char) -> Option<Token> {
+    Some(match ident {
+        'Y' => Token::FullYear,
+        'y' => Token::HalfYear,
+        'G' => Token::IsoYear,
+        'V' => Token::IsoWeek,
+        'W' => Token::MondayWeek,
+        'U' => Token::SundayWeek,
+        'm' => Token::FullMonth,
+        'd' => Token::Day,
+        'j' => Token::DayOfYear,
+        'e' => Token::Padded {
+            padding: Padding::Space,
+            width: None,
+            token: Box::new(Token::Day),
+        },
+        'H' => Token::TwentyFourHourDay,
+        'I' => Token::TwelveHourDay,
+        'M' => Token::Minute,
+        'S' => Token::Second,
+        _ => return None,
+    })
+}
+
+/// The token of `ident` preceded by a flag, a width or both, or `None` if
+/// it takes neither
+fn with_modifiers(ident: char, padding: Option<Padding>, width: Option<usize>) -> Option<Token> {
+    if width == Some(0) {
+        return None;
+    }
+    let (default, token) = match ident {
+        // Digits of a fraction are only ever cut, never padded
+        'f' if matches!(padding, None | Some(Padding::Zero)) => (Padding::Zero, Token::Fraction),
+        ident => match numeric_specifier(ident)? {
+            // `%0e` is the same as `%0d`
+            Token::Padded { padding, token, .. } => (padding, *token),
+            token => (Padding::Zero, token),
+        },
+    };
+    Some(Token::Padded {
+        padding: padding.unwrap_or(default),
+        width,
+        token: Box::new(token),
+    })
+}
+
+fn colon() -> Token {
+    Token::Literal {
+        pattern: String::from(":"),
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct DateTimeLexer<'a> {
     input: &'a str,
     rest: &'a str,
     byte: usize,
+    /// Byte offsets and opening characters, `[` or `(`, of every group
+    /// still open
+    open_groups: Vec<(usize, char)>,
+    /// Tokens left over from expanding a shorthand like `%T`, in reverse
+    pending: Vec<Token>,
+    /// Specifiers lexed as [`Token::Custom`] instead of rejected
+    custom: Vec<char>,
 }
 
 impl<'a> DateTimeLexer<'a> {
@@ -47,6 +282,37 @@ impl<'a> DateTimeLexer<'a> {
             input: src,
             rest: src,
             byte: 0,
+            open_groups: Vec::new(),
+            pending: Vec::new(),
+            custom: Vec::new(),
+        }
+    }
+    /// Consumes the flag and the width that may follow a `%`, like the `-`
+    /// and `2` of `%-2d`. Widths too large to parse are kept as zero, which
+    /// is just as invalid.
+    fn modifiers(&mut self) -> (Option<Padding>, Option<usize>) {
+        let padding = match self.rest.chars().next() {
+            Some('0') => Some(Padding::Zero),
+            Some('_') => Some(Padding::Space),
+            Some('-') => Some(Padding::Unpadded),
+            _ => None,
+        };
+        if padding.is_some() {
+            self.rest = &self.rest[1..];
+            self.byte += 1;
+        }
+        let digits = self.rest.bytes().take_while(u8::is_ascii_digit).count();
+        let width = (digits > 0).then(|| self.rest[..digits].parse::<u8>().map_or(0, usize::from));
+        self.rest = &self.rest[digits..];
+        self.byte += digits;
+        (padding, width)
+    }
+    /// Also accepts `specifiers`, which would otherwise be invalid. Those
+    /// the lexer already knows keep their meaning.
+    pub(crate) fn with_custom(self, specifiers: impl IntoIterator<Item = char>) -> Self {
+        Self {
+            custom: specifiers.into_iter().collect(),
+            ..self
         }
     }
 }
@@ -55,61 +321,147 @@ impl Iterator for DateTimeLexer<'_> {
     type Item = Result<Token, LexerError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending.pop() {
+            return Some(Ok(token));
+        }
         let mut chars = self.rest.chars();
-        let next = chars.next()?;
+        let Some(next) = chars.next() else {
+            // Report only the outermost unclosed group, then stop
+            let (start, _) = self.open_groups.drain(..).next()?;
+            return Some(Err(LexerError::UnclosedGroup {
+                src: self.input.to_string(),
+                at: (start, 1).into(),
+            }));
+        };
         self.byte += next.len_utf8();
         enum Started {
             Percent,
+            OpenGroup(char),
+            CloseGroup(char),
+            Alternative,
             Other(char),
         }
+        let in_alternation = matches!(self.open_groups.last(), Some((_, '(')));
         let started = match next {
             '%' => Started::Percent,
+            '[' | '(' => Started::OpenGroup(next),
+            ']' | ')' => Started::CloseGroup(next),
+            '|' if in_alternation => Started::Alternative,
             c => Started::Other(c),
         };
         match started {
             Started::Percent => {
                 self.rest = &self.rest[1..];
-                if self.rest.is_empty() {
+                let start = self.byte - next.len_utf8();
+                let (padding, width) = self.modifiers();
+                let Some(ident) = self.rest.chars().next() else {
                     return Some(Err(LexerError::UnexpectedEOF));
+                };
+                self.rest = &self.rest[ident.len_utf8()..];
+                self.byte += ident.len_utf8();
+                if padding.is_some() || width.is_some() {
+                    return Some(with_modifiers(ident, padding, width).ok_or_else(|| {
+                        LexerError::InvalidFormat {
+                            src: self.input.to_string(),
+                            at: (start, self.byte - start).into(),
+                        }
+                    }));
+                }
+                if let Some(token) = numeric_specifier(ident) {
+                    return Some(Ok(token));
                 }
-                assert!(!self.rest.is_empty());
-                let ident = chars.next().expect("Checked above");
-                self.rest = &self.rest[1..];
                 match ident {
-                    'Y' => Some(Ok(Token::FullYear)),
-                    'y' => Some(Ok(Token::HalfYear)),
-                    'm' => Some(Ok(Token::FullMonth)),
                     'B' => Some(Ok(Token::WrittenMonth)),
-                    'd' => Some(Ok(Token::Day)),
-                    'H' => Some(Ok(Token::TwentyFourHourDay)),
-                    'I' => Some(Ok(Token::TwelveHourDay)),
-                    'M' => Some(Ok(Token::Minute)),
-                    'S' => Some(Ok(Token::Second)),
+                    'b' => Some(Ok(Token::ShortMonth)),
+                    'A' => Some(Ok(Token::FullWeekday)),
+                    'a' => Some(Ok(Token::ShortWeekday)),
+                    'c' => Some(Ok(Token::LocaleDateTime)),
+                    'u' => Some(Ok(Token::IsoWeekdayNumber)),
+                    'w' => Some(Ok(Token::WeekdayNumber)),
+                    'f' => Some(Ok(Token::Fraction)),
+                    's' => Some(Ok(Token::Epoch)),
                     'p' => Some(Ok(Token::AmOrPm)),
+                    'z' => Some(Ok(Token::UtcOffset)),
+                    'Z' => Some(Ok(Token::TimeZoneName)),
+                    // `%R` is `%H:%M` and `%T` is `%H:%M:%S`
+                    'R' | 'T' => {
+                        if ident == 'T' {
+                            self.pending.extend([Token::Second, colon()]);
+                        }
+                        self.pending.extend([Token::Minute, colon()]);
+                        Some(Ok(Token::TwentyFourHourDay))
+                    }
+                    '%' => Some(Ok(Token::Literal {
+                        pattern: String::from("%"),
+                    })),
                     c if c.is_ascii_whitespace() => Some(Err(LexerError::InvalidWhitespace {
                         at: (
-                            self.byte - next.len_utf8(),
+                            self.byte - next.len_utf8() - ident.len_utf8(),
                             next.len_utf8() + ident.len_utf8(),
                         )
                             .into(),
                         src: self.input.to_string(),
                     })),
+                    c if self.custom.contains(&c) => Some(Ok(Token::Custom(c))),
                     c => Some(Err(LexerError::InvalidFormat {
                         src: self.input.to_string(),
-                        at: (self.byte - next.len_utf8(), next.len_utf8() + c.len_utf8()).into(),
+                        at: (
+                            self.byte - next.len_utf8() - c.len_utf8(),
+                            next.len_utf8() + c.len_utf8(),
+                        )
+                            .into(),
                     })),
                 }
             }
+            Started::OpenGroup(opening) => {
+                self.rest = &self.rest[1..];
+                self.open_groups.push((self.byte - 1, opening));
+                Some(Ok(match opening {
+                    '[' => Token::OptionalStart,
+                    _ => Token::AlternationStart,
+                }))
+            }
+            Started::CloseGroup(closing) => {
+                self.rest = &self.rest[1..];
+                let opening = if closing == ']' { '[' } else { '(' };
+                if self.open_groups.last().map(|(_, open)| *open) != Some(opening) {
+                    return Some(Err(LexerError::UnopenedGroup {
+                        src: self.input.to_string(),
+                        at: (self.byte - 1, 1).into(),
+                    }));
+                }
+                self.open_groups.pop();
+                Some(Ok(match closing {
+                    ']' => Token::OptionalEnd,
+                    _ => Token::AlternationEnd,
+                }))
+            }
+            Started::Alternative => {
+                self.rest = &self.rest[1..];
+                Some(Ok(Token::Alternative))
+            }
             Started::Other(c) => {
-                let mut pattern = String::from(c);
-                for next_char in chars {
-                    if next_char == '%' {
-                        break;
+                let mut pattern = String::new();
+                let mut consumed = 0;
+                let mut chars = self.rest.chars().peekable();
+                while let Some(next_char) = chars.next() {
+                    match next_char {
+                        '%' | '[' | ']' | '(' | ')' => break,
+                        '|' if in_alternation => break,
+                        // `\%` is a literal percent, and likewise for the
+                        // group characters. Any other backslash is kept as is
+                        '\\' if chars.peek().is_some_and(|c| "%[]()|".contains(*c)) => {
+                            pattern.push(chars.next().expect("Peeked above"));
+                            consumed += 2;
+                        }
+                        next_char => {
+                            pattern.push(next_char);
+                            consumed += next_char.len_utf8();
+                        }
                     }
-                    pattern.push(next_char);
                 }
-                self.rest = &self.rest[pattern.len()..];
-                self.byte += pattern.len();
+                self.rest = &self.rest[consumed..];
+                self.byte += consumed - c.len_utf8();
                 Some(Ok(Token::Literal { pattern }))
             }
         }
@@ -134,6 +486,20 @@ pub enum LexerError {
     },
     #[error("Unexpected EOF")]
     UnexpectedEOF,
+    #[error("Group is never closed")]
+    UnclosedGroup {
+        #[source_code]
+        src: String,
+        #[label("This group is never closed")]
+        at: SourceSpan,
+    },
+    #[error("Closing a group that was never opened")]
+    UnopenedGroup {
+        #[source_code]
+        src: String,
+        #[label("This does not close the innermost open group")]
+        at: SourceSpan,
+    },
 }
 
 #[cfg(test)]
@@ -208,7 +574,7 @@ mod tests {
     }
     #[test]
     fn test_error_conditions() -> TestResult {
-        let input = "%Z"; // Invalid format specifier
+        let input = "%Q"; // Invalid format specifier
         let mut lexer = DateTimeLexer::new(input);
         let result = lexer.next().ok_or(LexerError::UnexpectedEOF)?;
         // Should return an error for invalid format
@@ -226,7 +592,7 @@ mod tests {
     #[test]
     fn test_complex_patterns() -> TestResult {
         let input = "Date: %Y-%m-%d Time: %H:%M:%S";
-        let mut lexer = DateTimeLexer::new(input);
+        let lexer = DateTimeLexer::new(input);
         let mut tokens = Vec::new();
 
         for token in lexer {
@@ -275,4 +641,220 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_optional_groups() -> TestResult {
+        let lexer = DateTimeLexer::new("%d[ %H[:%M]]");
+        let tokens = lexer.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Day,
+                Token::OptionalStart,
+                Token::Literal {
+                    pattern: String::from(" "),
+                },
+                Token::TwentyFourHourDay,
+                Token::OptionalStart,
+                Token::Literal {
+                    pattern: String::from(":"),
+                },
+                Token::Minute,
+                Token::OptionalEnd,
+                Token::OptionalEnd,
+            ]
+        );
+
+        let mut lexer = DateTimeLexer::new("%d[%H");
+        assert!(matches!(lexer.next(), Some(Ok(Token::Day))));
+        assert!(matches!(lexer.next(), Some(Ok(Token::OptionalStart))));
+        assert!(matches!(lexer.next(), Some(Ok(Token::TwentyFourHourDay))));
+        assert!(matches!(
+            lexer.next(),
+            Some(Err(LexerError::UnclosedGroup { .. }))
+        ));
+        assert!(lexer.next().is_none());
+
+        let mut lexer = DateTimeLexer::new("%d]");
+        assert!(matches!(lexer.next(), Some(Ok(Token::Day))));
+        assert!(matches!(
+            lexer.next(),
+            Some(Err(LexerError::UnopenedGroup { .. }))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_escaped_percent() -> TestResult {
+        let tokens = DateTimeLexer::new(r"\%%Y").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal {
+                    pattern: String::from("%"),
+                },
+                Token::FullYear,
+            ]
+        );
+
+        let tokens = DateTimeLexer::new(r"100\% on %Y").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal {
+                    pattern: String::from("100% on "),
+                },
+                Token::FullYear,
+            ]
+        );
+
+        // `%%` is also a literal percent
+        let tokens = DateTimeLexer::new("%%%Y").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal {
+                    pattern: String::from("%"),
+                },
+                Token::FullYear,
+            ]
+        );
+
+        // A backslash not followed by `%` is an ordinary character, and an
+        // escaped percent never starts a specifier
+        let tokens = DateTimeLexer::new(r"a\b\%Y").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![Token::Literal {
+                pattern: String::from(r"a\b%Y"),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_compound_specifiers() -> TestResult {
+        let tokens = DateTimeLexer::new("%R").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![Token::TwentyFourHourDay, colon(), Token::Minute]
+        );
+
+        let tokens = DateTimeLexer::new("%Y %T").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::FullYear,
+                Token::Literal {
+                    pattern: String::from(" "),
+                },
+                Token::TwentyFourHourDay,
+                colon(),
+                Token::Minute,
+                colon(),
+                Token::Second,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_padding_flags() -> TestResult {
+        let tokens = DateTimeLexer::new("%-d%_H%0e%e").collect::<Result<Vec<_>, _>>()?;
+        let padded = |padding, token| Token::Padded {
+            padding,
+            width: None,
+            token: Box::new(token),
+        };
+        assert_eq!(
+            tokens,
+            vec![
+                padded(Padding::Unpadded, Token::Day),
+                padded(Padding::Space, Token::TwentyFourHourDay),
+                padded(Padding::Zero, Token::Day),
+                padded(Padding::Space, Token::Day),
+            ]
+        );
+        assert_eq!(tokens[0].as_format(), "%-d");
+        for input in ["%-B", "%_", "%-%"] {
+            let result = DateTimeLexer::new(input).collect::<Result<Vec<_>, _>>();
+            assert!(result.is_err(), "Failed on input: {input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_widths() -> TestResult {
+        let tokens = DateTimeLexer::new("%4d%-3H%_2e%3f%%2d").collect::<Result<Vec<_>, _>>()?;
+        let padded = |padding, width, token| Token::Padded {
+            padding,
+            width: Some(width),
+            token: Box::new(token),
+        };
+        assert_eq!(
+            tokens,
+            vec![
+                padded(Padding::Zero, 4, Token::Day),
+                padded(Padding::Unpadded, 3, Token::TwentyFourHourDay),
+                padded(Padding::Space, 2, Token::Day),
+                padded(Padding::Zero, 3, Token::Fraction),
+                // An escaped `%` keeps any digits after it as literals
+                Token::Literal {
+                    pattern: String::from("%"),
+                },
+                Token::Literal {
+                    pattern: String::from("2d"),
+                },
+            ]
+        );
+        assert_eq!(tokens[0].numeric_width(), Some(4));
+        for token in &tokens[..4] {
+            let format = token.as_format();
+            let relexed = DateTimeLexer::new(&format).collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                relexed,
+                std::slice::from_ref(token),
+                "Failed on format: {format}"
+            );
+        }
+        for input in ["%2B", "%-3f", "%00d", "%999d", "%2", "%2%"] {
+            let result = DateTimeLexer::new(input).collect::<Result<Vec<_>, _>>();
+            assert!(result.is_err(), "Failed on input: {input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_alternation() -> TestResult {
+        let tokens = DateTimeLexer::new("%Y(-|/)%m|").collect::<Result<Vec<_>, _>>()?;
+        let literal = |pattern: &str| Token::Literal {
+            pattern: pattern.to_string(),
+        };
+        assert_eq!(
+            tokens,
+            vec![
+                Token::FullYear,
+                Token::AlternationStart,
+                literal("-"),
+                Token::Alternative,
+                literal("/"),
+                Token::AlternationEnd,
+                Token::FullMonth,
+                // Outside of an alternation `|` is an ordinary character
+                literal("|"),
+            ]
+        );
+        let tokens = DateTimeLexer::new(r"\(%H\)\[\|").collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            tokens,
+            vec![literal("("), Token::TwentyFourHourDay, literal(")[|")]
+        );
+        assert_eq!(tokens[0].as_format(), r"\(");
+
+        for input in ["(%Y", "%Y)", "([)]", "[(])"] {
+            let result = DateTimeLexer::new(input).collect::<Result<Vec<_>, _>>();
+            assert!(result.is_err(), "Failed on input: {input}");
+        }
+        Ok(())
+    }
 }
@@ -1,3 +1,9 @@
 pub mod datetime;
+pub mod duration;
+pub(crate) mod formatter;
 pub(crate) mod interpreter;
+pub mod leap_seconds;
 pub(crate) mod lexer;
+pub mod locale;
+pub mod options;
+pub mod timezone;
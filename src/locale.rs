@@ -0,0 +1,131 @@
+use crate::{
+    datetime::DateOrder,
+    lexer::{DateTimeLexer, LexerError, Token},
+};
+
+/// Language specific words used when parsing and formatting dates.
+/// The default is [`Locale::ENGLISH`]. Custom locales can be built by
+/// overriding its fields.
+/// # Examples
+/// ```
+/// use datetime::locale::Locale;
+/// let locale = Locale {
+///     am: "a.m.",
+///     pm: "p.m.",
+///     ..Locale::default()
+/// };
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Locale {
+    /// Marker for 12-hour clock times before noon, matched by `%p`
+    pub am: &'static str,
+    /// Marker for 12-hour clock times from noon onwards, matched by `%p`
+    pub pm: &'static str,
+    /// Month names from January, matched by `%B`
+    pub months: [&'static str; 12],
+    /// Abbreviated month names from January, matched by `%b`
+    pub short_months: [&'static str; 12],
+    /// Weekday names from Monday, matched by `%A`
+    pub weekdays: [&'static str; 7],
+    /// Abbreviated weekday names from Monday, matched by `%a`
+    pub short_weekdays: [&'static str; 7],
+    /// The format `%c` stands for
+    pub date_time_format: &'static str,
+    /// The conventional layout of all-numeric dates, tried first by
+    /// [`Datetime::try_guess_with_locale`](crate::datetime::Datetime::try_guess_with_locale).
+    /// e.g.: [`DateOrder::Mdy`] in the US, [`DateOrder::Dmy`] in most of
+    /// Europe or [`DateOrder::Ymd`] for ISO 8601.
+    pub date_order: DateOrder,
+}
+
+impl Locale {
+    pub const ENGLISH: Locale = Locale {
+        am: "AM",
+        pm: "PM",
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+        weekdays: [
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+            "Sunday",
+        ],
+        short_weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+        date_time_format: "%a %b %e %H:%M:%S %Y",
+        date_order: DateOrder::Mdy,
+    };
+    pub const PORTUGUESE: Locale = Locale {
+        am: "AM",
+        pm: "PM",
+        months: [
+            "Janeiro",
+            "Fevereiro",
+            "Março",
+            "Abril",
+            "Maio",
+            "Junho",
+            "Julho",
+            "Agosto",
+            "Setembro",
+            "Outubro",
+            "Novembro",
+            "Dezembro",
+        ],
+        short_months: [
+            "Jan", "Fev", "Mar", "Abr", "Mai", "Jun", "Jul", "Ago", "Set", "Out", "Nov", "Dez",
+        ],
+        weekdays: [
+            "Segunda-feira",
+            "Terça-feira",
+            "Quarta-feira",
+            "Quinta-feira",
+            "Sexta-feira",
+            "Sábado",
+            "Domingo",
+        ],
+        short_weekdays: ["Seg", "Ter", "Qua", "Qui", "Sex", "Sáb", "Dom"],
+        date_time_format: "%a %e %b %Y %H:%M:%S",
+        date_order: DateOrder::Dmy,
+    };
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+impl Locale {
+    /// Lexes [`Locale::date_time_format`], which `%c` expands into. The
+    /// expansion may not contain `%c` itself.
+    pub(crate) fn date_time_tokens(&self) -> Result<Vec<Token>, LexerError> {
+        let format = self.date_time_format;
+        let tokens = DateTimeLexer::new(format).collect::<Result<Vec<_>, _>>()?;
+        if tokens.contains(&Token::LocaleDateTime) {
+            let start = format.find("%c").unwrap_or(0);
+            return Err(LexerError::InvalidFormat {
+                src: format.to_string(),
+                at: (start, 2).into(),
+            });
+        }
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,194 @@
+use std::{fmt, sync::Arc};
+
+use miette::Error;
+
+use crate::{locale::Locale, timezone::TzResolver};
+
+/// Default for [`ParseOptions::century_pivot`]
+pub(crate) const DEFAULT_CENTURY_PIVOT: usize = 25;
+
+/// How closely inputs must follow the format
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Strictness {
+    /// The input must be exactly what the format describes
+    #[default]
+    Strict,
+    /// Common mismatches are accepted when the intent is clear: a month
+    /// name where the format has `%m`, numbers other than years missing
+    /// their padding, any run of whitespace for whitespace in the format,
+    /// repeated separators, months past December and days past the end of
+    /// the month, which are moved back to the last one, weekday names cut
+    /// short, like `Mond`, and weekdays that don't match the date, which
+    /// are ignored. See [`Warning`] for telling which were needed.
+    Lenient,
+}
+
+/// Something [`Strictness::Lenient`] parsing accepted although the format
+/// did not describe it, as returned by [`Datetime::parse_with_warnings`]
+///
+/// [`Datetime::parse_with_warnings`]: crate::datetime::Datetime::parse_with_warnings
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Warning {
+    /// A month past December, moved back to it
+    MonthClamped { got: usize },
+    /// A day past the end of its month, moved back to the last one
+    DayClamped { got: usize, last: usize },
+    /// A month name where the format has `%m`
+    MonthName { name: String },
+    /// A number without the padding of its field, like `5` for `%d`
+    Unpadded { field: String, got: String },
+    /// A run of whitespace other than the one in the format
+    Whitespace { got: String },
+    /// A separator of the format repeated in the input, like `--`
+    RepeatedSeparator { separator: String },
+    /// A weekday name cut short, like `Mond`
+    WeekdayPrefix { prefix: String, weekday: String },
+    /// A weekday that does not match the date, which was kept
+    WeekdayMismatch { named: String, actual: String },
+}
+
+/// Field set by a custom specifier, see [`ParseOptions::with_custom`]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FieldUpdate {
+    Year(usize),
+    Month(usize),
+    Day(usize),
+    Hour(usize),
+    Minute(usize),
+    Second(usize),
+    Nanosecond(usize),
+    /// The input was consumed without setting anything
+    Nothing,
+}
+
+/// Reads the input of a custom specifier, returning the field it sets
+/// and the input left after it. Returning anything but a suffix of the
+/// input is an error.
+pub type CustomParser =
+    Arc<dyn for<'a> Fn(&'a str) -> Result<(FieldUpdate, &'a str), Error> + Send + Sync>;
+
+/// Settings that change how inputs are matched against a format.
+/// Every field has a default matching [`Datetime::from_str`], so only the
+/// relevant ones need to be set.
+/// # Examples
+/// ```
+/// use datetime::{datetime::Datetime, options::ParseOptions};
+/// let options = ParseOptions {
+///     century_pivot: 50,
+///     case_insensitive: true,
+///     ..ParseOptions::default()
+/// };
+/// let date = Datetime::parse_datetime_with_options("49-01-01 10 pm", "%y-%m-%d %I %p", &options).unwrap();
+/// assert_eq!((date.year, date.hour), (2049, 22));
+/// ```
+///
+/// [`Datetime::from_str`]: crate::datetime::Datetime::from_str
+#[derive(Clone)]
+pub struct ParseOptions {
+    /// Words used to match names, such as the `%p` markers
+    pub locale: Locale,
+    /// Two digit years (`%y`) below the pivot are placed in the 2000s and
+    /// the rest in the 1900s. The default of 25 reads `24` as 2024 and `25`
+    /// as 1925.
+    pub century_pivot: usize,
+    /// Match literals and names ignoring ASCII case, e.g.: `pm` for `PM`
+    pub case_insensitive: bool,
+    /// Whether inputs that stray from the format are rejected
+    pub strictness: Strictness,
+    /// Year that a week number (`%V`, `%U` or `%W`) belongs to when the
+    /// format has no `%G` or `%Y`, e.g.: for weekly schedules like `W01-1`.
+    /// Without one, such formats cannot be parsed.
+    pub reference_year: Option<usize>,
+    /// Gives the offset of the zone names matched by `%Z`. Without one,
+    /// formats using `%Z` cannot be parsed.
+    pub tz_resolver: Option<Arc<dyn TzResolver>>,
+    /// Set the hour, minute and second missing from the input to 23:59:59
+    /// instead of 00:00:00, e.g.: when reading the end of a date range
+    pub end_of_day: bool,
+    /// Accept inputs that stop right before a literal of the format,
+    /// leaving the fields after it unset, e.g.: `12:00` for `%H:%M:%S`
+    pub trailing_tolerant: bool,
+    /// Also accept months written as Roman numerals, from `I` to `XII`, for
+    /// `%m`, as in historical documents
+    pub roman_months: bool,
+    /// Remove whitespace and byte order marks around the input before
+    /// parsing it, as in text copied from spreadsheets
+    pub trim_input: bool,
+    /// Longest input in bytes that is read at all, to bound the work spent
+    /// on untrusted data. Unlimited by default.
+    pub max_input_len: Option<usize>,
+    /// Specifiers handled by user code, see [`ParseOptions::with_custom`]
+    pub custom_specifiers: Vec<(char, CustomParser)>,
+}
+
+impl ParseOptions {
+    /// Makes `%` followed by `specifier` in formats call `parser` on the
+    /// remaining input. Specifiers the crate already knows, like `d`, keep
+    /// their meaning, and formats using custom ones cannot be formatted.
+    /// # Examples
+    /// ```
+    /// use datetime::{datetime::Datetime, options::{FieldUpdate, ParseOptions}};
+    /// let options = ParseOptions::default().with_custom('q', |input: &str| {
+    ///     let rest = input.strip_prefix("Q3").ok_or_else(|| miette::miette!("Not Q3"))?;
+    ///     Ok((FieldUpdate::Month(7), rest))
+    /// });
+    /// let date = Datetime::parse_datetime_with_options("2024 Q3", "%Y %q", &options).unwrap();
+    /// assert_eq!(date.month, 7);
+    /// ```
+    pub fn with_custom(
+        mut self,
+        specifier: char,
+        parser: impl for<'a> Fn(&'a str) -> Result<(FieldUpdate, &'a str), Error>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.custom_specifiers.push((specifier, Arc::new(parser)));
+        self
+    }
+}
+
+impl fmt::Debug for ParseOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("locale", &self.locale)
+            .field("century_pivot", &self.century_pivot)
+            .field("case_insensitive", &self.case_insensitive)
+            .field("strictness", &self.strictness)
+            .field("reference_year", &self.reference_year)
+            .field("tz_resolver", &self.tz_resolver.as_ref().map(|_| ".."))
+            .field("end_of_day", &self.end_of_day)
+            .field("trailing_tolerant", &self.trailing_tolerant)
+            .field("roman_months", &self.roman_months)
+            .field("trim_input", &self.trim_input)
+            .field("max_input_len", &self.max_input_len)
+            .field(
+                "custom_specifiers",
+                &self
+                    .custom_specifiers
+                    .iter()
+                    .map(|(specifier, _)| specifier)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            century_pivot: DEFAULT_CENTURY_PIVOT,
+            case_insensitive: false,
+            strictness: Strictness::default(),
+            reference_year: None,
+            tz_resolver: None,
+            end_of_day: false,
+            trailing_tolerant: false,
+            roman_months: false,
+            trim_input: false,
+            max_input_len: None,
+            custom_specifiers: Vec::new(),
+        }
+    }
+}
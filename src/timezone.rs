@@ -0,0 +1,110 @@
+use crate::datetime::Datetime;
+
+/// Looks up the offset of named time zones, such as the IANA
+/// `America/Sao_Paulo`, so that `%Z` can be parsed without this crate
+/// bundling a time zone database.
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use datetime::{datetime::Datetime, options::ParseOptions, timezone::TzResolver};
+///
+/// struct Brazil;
+/// impl TzResolver for Brazil {
+///     fn offset_for(&self, name: &str, _at: &Datetime) -> Option<i32> {
+///         (name == "America/Sao_Paulo").then_some(-180)
+///     }
+/// }
+///
+/// let options = ParseOptions {
+///     tz_resolver: Some(Arc::new(Brazil)),
+///     ..ParseOptions::default()
+/// };
+/// let date = Datetime::parse_datetime_with_options(
+///     "2024-05-17 10:00 America/Sao_Paulo",
+///     "%Y-%m-%d %H:%M %Z",
+///     &options,
+/// )
+/// .unwrap();
+/// assert_eq!(date.offset_minutes, Some(-180));
+/// ```
+pub trait TzResolver: Send + Sync {
+    /// Offset from UTC in minutes, east positive, of the zone called `name`
+    /// at the local time `at`. Returns `None` for unknown zones.
+    fn offset_for(&self, name: &str, at: &Datetime) -> Option<i32>;
+    /// Same as [`TzResolver::offset_for`], but able to tell that `at` is
+    /// repeated or skipped by a daylight saving transition. The default
+    /// only reports [`LocalResult::Unique`] offsets, so resolvers that know
+    /// about transitions should override it.
+    fn resolve(&self, name: &str, at: &Datetime) -> Option<LocalResult> {
+        self.offset_for(name, at).map(LocalResult::Unique)
+    }
+}
+
+/// The offsets a local time may have in a time zone
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum LocalResult {
+    /// The time happens once, with this offset
+    Unique(i32),
+    /// The time happens twice, as clocks are set back, e.g.: 01:30 on the
+    /// night daylight saving time ends
+    Ambiguous { earliest: i32, latest: i32 },
+    /// The time never happens, as clocks skip over it, e.g.: 02:30 on the
+    /// night daylight saving time starts
+    Gap,
+}
+
+/// A time zone abbreviation like `EDT`, which names both an offset and
+/// whether it is daylight saving time. `%Z` reads those of
+/// [`ZoneAbbreviation::ALL`] without a [`TzResolver`].
+#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord, Hash)]
+pub struct ZoneAbbreviation {
+    pub name: &'static str,
+    /// Offset from UTC in minutes, east positive
+    pub offset_minutes: i32,
+    pub daylight_saving: bool,
+}
+
+impl ZoneAbbreviation {
+    /// Abbreviations read by `%Z` without a resolver, for North America,
+    /// Europe and a few other widely used zones. Ambiguous ones such as
+    /// `IST` are left to a [`TzResolver`], which is asked first anyway.
+    pub const ALL: &[ZoneAbbreviation] = &[
+        Self::new("UTC", 0, false),
+        Self::new("GMT", 0, false),
+        Self::new("EST", -300, false),
+        Self::new("EDT", -240, true),
+        Self::new("CST", -360, false),
+        Self::new("CDT", -300, true),
+        Self::new("MST", -420, false),
+        Self::new("MDT", -360, true),
+        Self::new("PST", -480, false),
+        Self::new("PDT", -420, true),
+        Self::new("AKST", -540, false),
+        Self::new("AKDT", -480, true),
+        Self::new("HST", -600, false),
+        Self::new("WET", 0, false),
+        Self::new("WEST", 60, true),
+        Self::new("BST", 60, true),
+        Self::new("CET", 60, false),
+        Self::new("CEST", 120, true),
+        Self::new("EET", 120, false),
+        Self::new("EEST", 180, true),
+        Self::new("JST", 540, false),
+        Self::new("AEST", 600, false),
+        Self::new("AEDT", 660, true),
+    ];
+    const fn new(name: &'static str, offset_minutes: i32, daylight_saving: bool) -> Self {
+        Self {
+            name,
+            offset_minutes,
+            daylight_saving,
+        }
+    }
+    /// Looks up `name` in [`ZoneAbbreviation::ALL`]
+    pub fn find(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|abbreviation| abbreviation.name == name)
+    }
+}